@@ -0,0 +1,156 @@
+// Shared by the benches, each of them only uses some of it
+#![allow(dead_code)]
+
+use std::path::Path;
+
+use advent_of_code_2021_rust::inputs::{find_input, DEFAULT_INPUTS_DIRECTORY};
+
+// The real input of a day, from the inputs directory or embedded in the library
+pub fn real_input(day: u8) -> Option<String> {
+    return find_input(day, Path::new(DEFAULT_INPUTS_DIRECTORY))
+        .ok()
+        .map(|input| input.data);
+}
+
+// xorshift64, no need for a rand dependency and the inputs are the same on every run
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        return Rng(seed.max(1));
+    }
+
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        return self.0;
+    }
+
+    // In 0..n
+    pub fn below(&mut self, n: u64) -> u64 {
+        return self.next() % n;
+    }
+}
+
+/**
+ * Synthetic inputs, much bigger than the real ones, to see how the
+ * solutions scale. They are valid inputs for the days, with a few caveats
+ * written next to the generators.
+ */
+pub fn depths(count: usize) -> String {
+    let mut rng = Rng::new(1);
+    let mut depth: i64 = 1000;
+    let mut data = String::new();
+    for _ in 0..count {
+        depth = (depth + rng.below(21) as i64 - 8).max(0);
+        data.push_str(&format!("{}\n", depth));
+    }
+    return data;
+}
+
+pub fn submarine_instructions(count: usize) -> String {
+    let mut rng = Rng::new(2);
+    let mut data = String::new();
+    for _ in 0..count {
+        // More downs than ups so the depth stays positive
+        let direction = match rng.below(6) {
+            0 => "up",
+            1 | 2 => "down",
+            _ => "forward",
+        };
+        data.push_str(&format!("{} {}\n", direction, rng.below(9) + 1));
+    }
+    return data;
+}
+
+// The bits are biased per column so there is never a tie for part 1
+pub fn diagnostic(count: usize, number_of_digits: usize) -> String {
+    let mut rng = Rng::new(3);
+    let mut data = String::new();
+    for _ in 0..count {
+        for column in 0..number_of_digits {
+            let one = if column % 2 == 0 {
+                rng.below(4) != 0
+            } else {
+                rng.below(4) == 0
+            };
+            data.push(if one { '1' } else { '0' });
+        }
+        data.push('\n');
+    }
+    return data;
+}
+
+// Horizontal, vertical and diagonal segments inside the 1000x1000 map
+pub fn segments(count: usize) -> String {
+    let mut rng = Rng::new(5);
+    let mut data = String::new();
+    for _ in 0..count {
+        let x1 = rng.below(1000) as i64;
+        let y1 = rng.below(1000) as i64;
+        let length = rng.below(200) as i64;
+        let (x2, y2) = match rng.below(3) {
+            0 => ((x1 + length).min(999), y1),
+            1 => (x1, (y1 + length).min(999)),
+            _ => {
+                let length = length.min(999 - x1).min(999 - y1);
+                (x1 + length, y1 + length)
+            }
+        };
+        data.push_str(&format!("{},{} -> {},{}\n", x1, y1, x2, y2));
+    }
+    return data;
+}
+
+pub fn fishes(count: usize) -> String {
+    let mut rng = Rng::new(6);
+    return (0..count)
+        .map(|_| (rng.below(5) + 1).to_string())
+        .collect::<Vec<String>>()
+        .join(",");
+}
+
+pub fn crabs(count: usize) -> String {
+    let mut rng = Rng::new(7);
+    return (0..count)
+        .map(|_| rng.below(2000).to_string())
+        .collect::<Vec<String>>()
+        .join(",");
+}
+
+// Also used for the energy levels of day 11
+pub fn heightmap(width: usize, height: usize) -> String {
+    let mut rng = Rng::new(9);
+    let mut data = String::new();
+    for _ in 0..height {
+        for _ in 0..width {
+            data.push_str(&rng.below(10).to_string());
+        }
+        data.push('\n');
+    }
+    return data;
+}
+
+// Mostly incomplete lines, with a corrupted one now and then
+pub fn navigation_subsystem(count: usize, length: usize) -> String {
+    let mut rng = Rng::new(10);
+    let brackets = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
+    let mut data = String::new();
+    for _ in 0..count {
+        let mut stack = vec![];
+        for _ in 0..length {
+            if stack.is_empty() || rng.below(3) != 0 {
+                let (open, close) = brackets[rng.below(4) as usize];
+                stack.push(close);
+                data.push(open);
+            } else if rng.below(100) == 0 {
+                data.push(brackets[rng.below(4) as usize].1);
+            } else {
+                data.push(stack.pop().unwrap());
+            }
+        }
+        data.push('\n');
+    }
+    return data;
+}
@@ -0,0 +1,84 @@
+//! Every part of every day, on the real inputs and on bigger synthetic ones.
+//!
+//! Run with `cargo bench --bench days`, or `cargo bench --bench days -- day_05`
+//! for a single day.
+
+#![allow(clippy::needless_return)]
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use advent_of_code_2021_rust::day_01::Day01;
+use advent_of_code_2021_rust::day_02::Day02;
+use advent_of_code_2021_rust::day_03::Day03;
+use advent_of_code_2021_rust::day_04::Day04;
+use advent_of_code_2021_rust::day_05::Day05;
+use advent_of_code_2021_rust::day_06::Day06;
+use advent_of_code_2021_rust::day_07::Day07;
+use advent_of_code_2021_rust::day_08::Day08;
+use advent_of_code_2021_rust::day_09::Day09;
+use advent_of_code_2021_rust::day_10::Day10;
+use advent_of_code_2021_rust::day_11::Day11;
+use advent_of_code_2021_rust::day_12::Day12;
+use advent_of_code_2021_rust::Solution;
+
+mod common;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Parts {
+    Both,
+    // For the synthetic inputs where part 2 has no answer
+    OnlyFirst,
+}
+
+// Parsing and the parts are measured separately, the parts get an already parsed input
+fn bench_input<S: Solution>(c: &mut Criterion, name: &str, data: &str, parts: Parts) {
+    let mut group = c.benchmark_group(format!("day_{:02}", S::DAY));
+    group.bench_with_input(BenchmarkId::new("parse", name), data, |b, data| {
+        b.iter(|| S::parse(black_box(data)).is_ok())
+    });
+    let input = S::parse(data).expect("Invalid bench input");
+    group.bench_with_input(BenchmarkId::new("part_1", name), &input, |b, input| {
+        b.iter(|| S::part_1(black_box(input)))
+    });
+    if parts == Parts::Both {
+        group.bench_with_input(BenchmarkId::new("part_2", name), &input, |b, input| {
+            b.iter(|| S::part_2(black_box(input)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_day<S: Solution>(c: &mut Criterion, synthetic: Option<(String, Parts)>) {
+    if let Some(data) = common::real_input(S::DAY) {
+        bench_input::<S>(c, "real", &data, Parts::Both);
+    }
+    if let Some((data, parts)) = synthetic {
+        bench_input::<S>(c, "synthetic", &data, parts);
+    }
+}
+
+fn days(c: &mut Criterion) {
+    bench_day::<Day01>(c, Some((common::depths(1_000_000), Parts::Both)));
+    bench_day::<Day02>(
+        c,
+        Some((common::submarine_instructions(1_000_000), Parts::Both)),
+    );
+    bench_day::<Day03>(c, Some((common::diagnostic(100_000, 16), Parts::Both)));
+    bench_day::<Day04>(c, None);
+    bench_day::<Day05>(c, Some((common::segments(20_000), Parts::Both)));
+    bench_day::<Day06>(c, Some((common::fishes(1_000_000), Parts::Both)));
+    bench_day::<Day07>(c, Some((common::crabs(1_000_000), Parts::Both)));
+    bench_day::<Day08>(c, None);
+    bench_day::<Day09>(c, Some((common::heightmap(1000, 1000), Parts::Both)));
+    bench_day::<Day10>(
+        c,
+        Some((common::navigation_subsystem(20_000, 100), Parts::Both)),
+    );
+    // Big boards take a long time to synchronise, if they ever do
+    bench_day::<Day11>(c, Some((common::heightmap(100, 100), Parts::OnlyFirst)));
+    // Day 12 is exponential in the size of the caves
+    bench_day::<Day12>(c, None);
+}
+
+criterion_group!(benches, days);
+criterion_main!(benches);
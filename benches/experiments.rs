@@ -0,0 +1,156 @@
+//! The alternative implementations side by side: parsing everything or
+//! streaming for day 1, rayon or not for days 2 and 3, the dense and sparse
+//! maps of day 5, and the slow and faster simulations of day 6.
+//!
+//! Run with `cargo bench --bench experiments`.
+
+#![allow(clippy::needless_return)]
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use advent_of_code_2021_rust::day_01::{
+    count_increases, count_increases_in, string_to_vec_of_ints,
+};
+use advent_of_code_2021_rust::day_02::{
+    final_position_product, final_position_product_sequential, final_position_with_aim_product,
+    final_position_with_aim_product_sequential, parse_submarine_instructions,
+    parse_submarine_instructions_sequential,
+};
+use advent_of_code_2021_rust::day_03::parse_diagnostic;
+use advent_of_code_2021_rust::day_05::{
+    count_overlaps_dense, count_overlaps_sparse, parse_segments,
+};
+use advent_of_code_2021_rust::day_06::{
+    faster_fishes_simulation, parse_input_numbers, slow_fishes_simulation,
+};
+
+mod common;
+
+// The real input and growing synthetic ones, to find where rayon starts to pay off
+fn sizes(day: u8, synthetic: impl Fn(usize) -> String) -> Vec<(String, String)> {
+    let mut inputs = vec![];
+    if let Some(data) = common::real_input(day) {
+        inputs.push((String::from("real"), data));
+    }
+    for size in [10_000, 100_000, 1_000_000] {
+        inputs.push((size.to_string(), synthetic(size)));
+    }
+    return inputs;
+}
+
+fn day_01(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day_01_streaming");
+    for (name, data) in sizes(1, common::depths) {
+        group.bench_with_input(BenchmarkId::new("vec", &name), &data, |b, data| {
+            b.iter(|| count_increases(string_to_vec_of_ints(black_box(data)).unwrap(), 3))
+        });
+        group.bench_with_input(BenchmarkId::new("reader", &name), &data, |b, data| {
+            b.iter(|| count_increases_in(black_box(data.as_bytes()), 3).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn day_02(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day_02_rayon");
+    for (name, data) in sizes(2, common::submarine_instructions) {
+        group.bench_with_input(BenchmarkId::new("parse_rayon", &name), &data, |b, data| {
+            b.iter(|| parse_submarine_instructions(black_box(data)))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("parse_sequential", &name),
+            &data,
+            |b, data| b.iter(|| parse_submarine_instructions_sequential(black_box(data))),
+        );
+
+        let instructions = parse_submarine_instructions(&data).unwrap();
+        group.bench_with_input(
+            BenchmarkId::new("part_1_rayon", &name),
+            &instructions,
+            |b, instructions| b.iter(|| final_position_product(black_box(instructions))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("part_1_sequential", &name),
+            &instructions,
+            |b, instructions| b.iter(|| final_position_product_sequential(black_box(instructions))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("part_2_rayon", &name),
+            &instructions,
+            |b, instructions| b.iter(|| final_position_with_aim_product(black_box(instructions))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("part_2_sequential", &name),
+            &instructions,
+            |b, instructions| {
+                b.iter(|| final_position_with_aim_product_sequential(black_box(instructions)))
+            },
+        );
+    }
+    group.finish();
+}
+
+fn day_03(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day_03_rayon");
+    for (name, data) in sizes(3, |size| common::diagnostic(size, 12)) {
+        let diagnostic = parse_diagnostic(&data).unwrap();
+        group.bench_with_input(
+            BenchmarkId::new("column_sums_rayon", &name),
+            &diagnostic,
+            |b, diagnostic| b.iter(|| black_box(diagnostic).column_sums()),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("column_sums_sequential", &name),
+            &diagnostic,
+            |b, diagnostic| b.iter(|| black_box(diagnostic).column_sums_sequential()),
+        );
+    }
+    group.finish();
+}
+
+fn day_05(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day_05_map");
+    let mut inputs = vec![];
+    if let Some(data) = common::real_input(5) {
+        inputs.push((String::from("real"), data));
+    }
+    inputs.push((String::from("20000"), common::segments(20_000)));
+    for (name, data) in inputs {
+        let segments = parse_segments(&data).unwrap();
+        group.bench_with_input(
+            BenchmarkId::new("dense", &name),
+            &segments,
+            |b, segments| b.iter(|| count_overlaps_dense(black_box(segments), true)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("sparse", &name),
+            &segments,
+            |b, segments| b.iter(|| count_overlaps_sparse(black_box(segments), true)),
+        );
+    }
+    group.finish();
+}
+
+fn day_06(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day_06_simulation");
+    let fishes = common::real_input(6)
+        .and_then(|data| parse_input_numbers(&data).ok())
+        .unwrap_or_else(|| parse_input_numbers(&common::fishes(300)).unwrap());
+    // The slow one grows exponentially, 80 generations like part 1 is already a lot
+    for generation in [20, 40, 80] {
+        group.bench_with_input(
+            BenchmarkId::new("slow", generation),
+            &generation,
+            |b, &generation| b.iter(|| slow_fishes_simulation(black_box(&fishes), generation)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("faster", generation),
+            &generation,
+            |b, &generation| b.iter(|| faster_fishes_simulation(black_box(&fishes), generation)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, day_01, day_02, day_03, day_05, day_06);
+criterion_main!(benches);
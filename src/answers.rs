@@ -0,0 +1,204 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::error::Error;
+use crate::solution::{Day, Part};
+
+pub const DEFAULT_ANSWERS_FILE: &str = "answers.toml";
+
+pub type Answers = BTreeMap<(u8, Part), i64>;
+
+/**
+ * The answers are recorded in a TOML file, one table per part:
+ *
+ * ```toml
+ * [[answer]]
+ * day = 1
+ * part = 1
+ * value = 1709
+ * ```
+ */
+#[derive(Deserialize)]
+struct AnswersFile {
+    #[serde(default)]
+    answer: Vec<RecordedAnswer>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RecordedAnswer {
+    day: u8,
+    part: u8,
+    value: i64,
+}
+
+pub fn parse_answers(data: &str) -> Result<Answers, String> {
+    let file: AnswersFile = toml::from_str(data).map_err(|error| error.to_string())?;
+    let mut answers = Answers::new();
+    for answer in file.answer {
+        let part = match answer.part {
+            1 => Part::One,
+            2 => Part::Two,
+            _ => {
+                return Err(format!(
+                    "invalid part {} for day {}",
+                    answer.part, answer.day
+                ))
+            }
+        };
+        if answers.insert((answer.day, part), answer.value).is_some() {
+            return Err(format!(
+                "day {}, part {} is recorded twice",
+                answer.day, answer.part
+            ));
+        }
+    }
+    return Ok(answers);
+}
+
+pub fn load_answers(path: &Path) -> io::Result<Answers> {
+    let data = fs::read_to_string(path)?;
+    return parse_answers(&data).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Correct,
+    Mismatch,
+    // Nothing recorded for this part yet
+    Missing,
+    Failed,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let status = match self {
+            Status::Correct => "ok",
+            Status::Mismatch => "MISMATCH",
+            Status::Missing => "MISSING",
+            Status::Failed => "FAILED",
+        };
+        // pad, so the status can be aligned in the table
+        return f.pad(status);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verification {
+    pub day: u8,
+    pub part: Part,
+    pub expected: Option<i64>,
+    pub actual: Result<i64, Error>,
+}
+
+impl Verification {
+    pub fn status(&self) -> Status {
+        return match (&self.actual, self.expected) {
+            (Err(_), _) => Status::Failed,
+            (Ok(_), None) => Status::Missing,
+            (Ok(actual), Some(expected)) if *actual == expected => Status::Correct,
+            (Ok(_), Some(_)) => Status::Mismatch,
+        };
+    }
+}
+
+// The input is parsed once, when it fails every part fails with the parse error
+pub fn verify(day: &Day, data: &str, parts: &[Part], answers: &Answers) -> Vec<Verification> {
+    let answers_of_day: Vec<Result<i64, Error>> = match day.run_parts(data, parts) {
+        Ok(runs) => runs
+            .into_iter()
+            .map(|run| run.map(|run| run.answer))
+            .collect(),
+        Err(error) => vec![Err(error); parts.len()],
+    };
+    return parts
+        .iter()
+        .zip(answers_of_day)
+        .map(|(&part, actual)| Verification {
+            day: day.day,
+            part,
+            expected: answers.get(&(day.day, part)).copied(),
+            actual,
+        })
+        .collect();
+}
+
+// The verifications as a table, one line per part
+pub fn table(verifications: &[Verification]) -> String {
+    let mut table = format!(
+        "{:>3} {:>4} {:>16} {:>16} {}\n",
+        "Day", "Part", "expected", "actual", "status"
+    );
+    for verification in verifications {
+        table.push_str(&format!(
+            "{:>3} {:>4} {:>16} {:>16} {}\n",
+            verification.day,
+            verification.part,
+            verification
+                .expected
+                .map_or(String::from("-"), |expected| expected.to_string()),
+            verification
+                .actual
+                .as_ref()
+                .map_or(String::from("error"), |actual| actual.to_string()),
+            verification.status()
+        ));
+    }
+    return table;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_answers() {
+        let answers = parse_answers(
+            "[[answer]]\nday = 6\npart = 2\nvalue = 26984457539\n\n[[answer]]\nday = 1\npart = 1\nvalue = 7",
+        )
+        .unwrap();
+        assert_eq!(answers.len(), 2);
+        assert_eq!(answers[&(6, Part::Two)], 26984457539);
+        assert_eq!(answers[&(1, Part::One)], 7);
+        assert_eq!(parse_answers(""), Ok(Answers::new()));
+
+        assert_eq!(
+            parse_answers("[[answer]]\nday = 1\npart = 3\nvalue = 7"),
+            Err(String::from("invalid part 3 for day 1"))
+        );
+        assert_eq!(
+            parse_answers("[[answer]]\nday = 1\npart = 1\nvalue = 7\n[[answer]]\nday = 1\npart = 1\nvalue = 8"),
+            Err(String::from("day 1, part 1 is recorded twice"))
+        );
+        assert!(parse_answers("[[answer]]\nday = 1\npart = 1").is_err());
+    }
+
+    #[test]
+    fn test_verification_status() {
+        let verification = |expected, actual| Verification {
+            day: 1,
+            part: Part::One,
+            expected,
+            actual,
+        };
+        assert_eq!(verification(Some(7), Ok(7)).status(), Status::Correct);
+        assert_eq!(verification(Some(7), Ok(8)).status(), Status::Mismatch);
+        assert_eq!(verification(None, Ok(8)).status(), Status::Missing);
+        assert_eq!(
+            verification(Some(7), Err(Error::no_solution(1, "nope"))).status(),
+            Status::Failed
+        );
+
+        assert_eq!(
+            table(&[verification(Some(7), Ok(8))]),
+            "Day Part         expected           actual status
+  1    1                7                8 MISMATCH
+"
+        );
+    }
+}
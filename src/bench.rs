@@ -0,0 +1,210 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::error::Error;
+use crate::solution::{Day, Part};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Measurement {
+    pub day: u8,
+    pub part: Part,
+    pub min: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+}
+
+impl Measurement {
+    pub fn from_samples(day: u8, part: Part, samples: &[Duration]) -> Measurement {
+        let mut samples = samples.to_vec();
+        samples.sort_unstable();
+        // Nearest rank, good enough for benchmarks
+        let rank = |percentile: usize| {
+            let index = (samples.len() * percentile).div_ceil(100);
+            return samples[index.clamp(1, samples.len()) - 1];
+        };
+        return Measurement {
+            day,
+            part,
+            min: samples[0],
+            median: rank(50),
+            p95: rank(95),
+        };
+    }
+}
+
+// Run the parts many times, parsing included, once per iteration for all the parts
+pub fn bench(
+    day: &Day,
+    data: &str,
+    parts: &[Part],
+    iterations: usize,
+) -> Result<Vec<Measurement>, Error> {
+    let mut samples = vec![Vec::with_capacity(iterations); parts.len()];
+    for _ in 0..iterations.max(1) {
+        for (part_samples, run) in samples.iter_mut().zip(day.run_parts(data, parts)?) {
+            part_samples.push(run?.total_time());
+        }
+    }
+    return Ok(parts
+        .iter()
+        .zip(&samples)
+        .map(|(&part, samples)| Measurement::from_samples(day.day, part, samples))
+        .collect());
+}
+
+pub type Baseline = BTreeMap<(u8, Part), Measurement>;
+
+/**
+ * Baselines are plain text files, one measurement per line with the
+ * durations in nanoseconds:
+ *
+ * ```text
+ * # day part min median p95
+ * 1 1 15200 16000 18300
+ * ```
+ */
+pub fn write_baseline(measurements: &[Measurement]) -> String {
+    let mut baseline = String::from("# day part min median p95\n");
+    for m in measurements {
+        baseline.push_str(&format!(
+            "{} {} {} {} {}\n",
+            m.day,
+            m.part,
+            m.min.as_nanos(),
+            m.median.as_nanos(),
+            m.p95.as_nanos()
+        ));
+    }
+    return baseline;
+}
+
+pub fn parse_baseline(data: &str) -> Result<Baseline, String> {
+    let mut baseline = Baseline::new();
+    for (line_index, line) in data.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = || format!("invalid baseline line {}: {}", line_index + 1, line);
+        let numbers = line
+            .split_whitespace()
+            .map(|n| n.parse::<u64>())
+            .collect::<Result<Vec<u64>, _>>()
+            .map_err(|_| invalid())?;
+        let (day, part, min, median, p95) = match numbers[..] {
+            [day, 1, min, median, p95] => (day, Part::One, min, median, p95),
+            [day, 2, min, median, p95] => (day, Part::Two, min, median, p95),
+            _ => return Err(invalid()),
+        };
+        let day = u8::try_from(day).map_err(|_| invalid())?;
+        baseline.insert(
+            (day, part),
+            Measurement {
+                day,
+                part,
+                min: Duration::from_nanos(min),
+                median: Duration::from_nanos(median),
+                p95: Duration::from_nanos(p95),
+            },
+        );
+    }
+    return Ok(baseline);
+}
+
+pub fn save_baseline(path: &Path, measurements: &[Measurement]) -> io::Result<()> {
+    return fs::write(path, write_baseline(measurements));
+}
+
+pub fn load_baseline(path: &Path) -> io::Result<Baseline> {
+    let data = fs::read_to_string(path)?;
+    return parse_baseline(&data)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error));
+}
+
+// Relative change of the medians, 0.1 means 10% slower than the baseline
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Comparison {
+    pub change: f64,
+    pub regression: bool,
+}
+
+impl Comparison {
+    pub fn new(measurement: &Measurement, baseline: &Measurement, threshold: f64) -> Self {
+        let before = baseline.median.as_secs_f64();
+        let after = measurement.median.as_secs_f64();
+        let change = if before > 0.0 {
+            (after - before) / before
+        } else {
+            0.0
+        };
+        return Comparison {
+            change,
+            regression: change > threshold,
+        };
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:+.1}%", self.change * 100.0)?;
+        if self.regression {
+            write!(f, " REGRESSION")?;
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn micros(values: &[u64]) -> Vec<Duration> {
+        return values.iter().map(|&v| Duration::from_micros(v)).collect();
+    }
+
+    #[test]
+    fn test_measurement_from_samples() {
+        let samples = micros(&(1..=100).rev().collect::<Vec<u64>>());
+        let m = Measurement::from_samples(3, Part::Two, &samples);
+        assert_eq!(m.min, Duration::from_micros(1));
+        assert_eq!(m.median, Duration::from_micros(50));
+        assert_eq!(m.p95, Duration::from_micros(95));
+
+        let m = Measurement::from_samples(3, Part::Two, &micros(&[7]));
+        assert_eq!((m.min, m.median, m.p95), (m.min, m.min, m.min));
+    }
+
+    #[test]
+    fn test_baseline_round_trip() {
+        let measurements = [
+            Measurement::from_samples(1, Part::One, &micros(&[10, 12, 30])),
+            Measurement::from_samples(12, Part::Two, &micros(&[1000])),
+        ];
+        let baseline = parse_baseline(&write_baseline(&measurements)).unwrap();
+        assert_eq!(baseline.len(), 2);
+        assert_eq!(baseline[&(1, Part::One)], measurements[0]);
+        assert_eq!(baseline[&(12, Part::Two)], measurements[1]);
+
+        assert!(parse_baseline("1 3 10 10 10").is_err());
+        assert!(parse_baseline("1 1 ten 10 10").is_err());
+    }
+
+    #[test]
+    fn test_comparison() {
+        let baseline = Measurement::from_samples(1, Part::One, &micros(&[100]));
+        let slower = Measurement::from_samples(1, Part::One, &micros(&[120]));
+        let faster = Measurement::from_samples(1, Part::One, &micros(&[90]));
+
+        let comparison = Comparison::new(&slower, &baseline, 0.1);
+        assert!(comparison.regression);
+        assert_eq!(comparison.to_string(), "+20.0% REGRESSION");
+
+        let comparison = Comparison::new(&faster, &baseline, 0.1);
+        assert!(!comparison.regression);
+        assert_eq!(comparison.to_string(), "-10.0%");
+    }
+}
@@ -0,0 +1,534 @@
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
+
+use advent_of_code_2021_rust::answers::DEFAULT_ANSWERS_FILE;
+use advent_of_code_2021_rust::day_05;
+use advent_of_code_2021_rust::inputs::DEFAULT_INPUTS_DIRECTORY;
+use advent_of_code_2021_rust::Part;
+
+// Exit codes, 2 is also the one used by clap for invalid command lines
+pub const EXIT_INVALID_ARGUMENTS: u8 = 2;
+pub const EXIT_UNKNOWN_DAY: u8 = 3;
+pub const EXIT_INPUT_ERROR: u8 = 4;
+pub const EXIT_PARSE_ERROR: u8 = 5;
+pub const EXIT_NO_SOLUTION: u8 = 6;
+pub const EXIT_REGRESSION: u8 = 7;
+pub const EXIT_WRONG_ANSWER: u8 = 8;
+// A file that could not be written, like the heatmap image
+pub const EXIT_OUTPUT_ERROR: u8 = 9;
+
+#[derive(Parser, Debug)]
+#[command(name = "aoc", about = "Advent of code 2021 in Rust")]
+pub struct Cli {
+    // Without subcommand, everything is run like before
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Run the solutions of some days
+    Run(RunArgs),
+    /// Run the solutions of some days many times and report their timings
+    Bench(BenchArgs),
+    /// Check the answers of some days against the recorded ones
+    Verify(VerifyArgs),
+    /// Watch the octopuses of day 11 flash in the terminal
+    ///
+    /// Any rectangular grid of digits works as an input.
+    Visualize(VisualizeArgs),
+    /// Draw the vent lines of day 5 as a heatmap
+    ///
+    /// The image covers the segments of the layer, and can't be larger than
+    /// 4096x4096 pixels.
+    Heatmap(HeatmapArgs),
+    /// Explore the depths of day 1 with any window sizes
+    ///
+    /// The input is streamed, it can be larger than the memory.
+    Sonar(SonarArgs),
+    /// Export the positions of the day 2 submarine with the movements of both parts
+    Trajectory(TrajectoryArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct RunArgs {
+    #[command(flatten)]
+    pub selection: Selection,
+
+    /// Report the parsing and solving times of each part
+    #[arg(short, long)]
+    pub time: bool,
+
+    /// Run the days at the same time on the rayon thread pool. The output is
+    /// still in the order of the days, but the times of each part are longer
+    /// since the days share the cores.
+    #[arg(long)]
+    pub parallel: bool,
+
+    /// Output format, json and csv always include the times in nanoseconds
+    #[arg(short, long, value_enum, default_value_t = Format::Text)]
+    pub format: Format,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+    Csv,
+}
+
+#[derive(Args, Debug)]
+pub struct BenchArgs {
+    #[command(flatten)]
+    pub selection: Selection,
+
+    /// Number of runs of each part
+    #[arg(short = 'n', long, default_value_t = 100)]
+    pub iterations: usize,
+
+    /// Compare the medians with a baseline saved by --save-baseline
+    #[arg(long, value_name = "FILE")]
+    pub baseline: Option<PathBuf>,
+
+    /// Save the results as a baseline
+    #[arg(long, value_name = "FILE")]
+    pub save_baseline: Option<PathBuf>,
+
+    /// Slowdown of the median, in percent, reported as a regression
+    #[arg(long, default_value_t = 10.0)]
+    pub threshold: f64,
+}
+
+#[derive(Args, Debug)]
+pub struct VerifyArgs {
+    #[command(flatten)]
+    pub selection: Selection,
+
+    /// File with the expected answers
+    #[arg(long, value_name = "FILE", default_value = DEFAULT_ANSWERS_FILE)]
+    pub answers: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct VisualizeArgs {
+    #[command(flatten)]
+    pub inputs: InputArgs,
+
+    /// Steps per second, between 0.5 and 120
+    #[arg(long, default_value_t = 10.0, value_parser = parse_fps)]
+    pub fps: f64,
+
+    /// Start paused, to go step by step
+    #[arg(long)]
+    pub paused: bool,
+
+    /// Connect the edges of the grid, like on a torus
+    #[arg(long)]
+    pub wrap: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct HeatmapArgs {
+    /// The PPM image to write
+    #[arg(short, long, value_name = "FILE")]
+    pub output: PathBuf,
+
+    /// The segments to draw
+    #[arg(short, long, value_enum, default_value_t = Layer::All)]
+    pub layer: Layer,
+
+    #[command(flatten)]
+    pub inputs: InputArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct SonarArgs {
+    /// Sizes of the sliding windows, the puzzle uses 1 and 3
+    #[arg(
+        short,
+        long = "window",
+        value_name = "SIZE",
+        value_delimiter = ',',
+        default_values_t = [1, 3],
+        value_parser = parse_window
+    )]
+    pub windows: Vec<usize>,
+
+    /// Number of largest jumps to report for each window
+    #[arg(short, long, default_value_t = 3)]
+    pub jumps: usize,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = Format::Text)]
+    pub format: Format,
+
+    #[command(flatten)]
+    pub inputs: InputArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct TrajectoryArgs {
+    /// Write the positions after each instruction to a CSV file,
+    /// they are printed when there is neither --csv nor --svg
+    #[arg(long, value_name = "FILE")]
+    pub csv: Option<PathBuf>,
+
+    /// Plot the depth profiles of both parts to an SVG file
+    #[arg(long, value_name = "FILE")]
+    pub svg: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub inputs: InputArgs,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    /// All the segments
+    All,
+    /// Only the horizontal and vertical segments
+    Straight,
+    /// Only the diagonal segments
+    Diagonal,
+}
+
+impl From<Layer> for day_05::Layer {
+    fn from(layer: Layer) -> Self {
+        return match layer {
+            Layer::All => day_05::Layer::All,
+            Layer::Straight => day_05::Layer::Straight,
+            Layer::Diagonal => day_05::Layer::Diagonal,
+        };
+    }
+}
+
+// The days, parts and inputs to run
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("selection").required(true).args(["day", "all"])))]
+pub struct Selection {
+    /// Day to run: 9, a range like 3..7 (7 excluded) or 3..=7 (7 included),
+    /// or a comma separated list of those. Only one day with --input.
+    #[arg(short, long, value_parser = parse_day_range, value_delimiter = ',')]
+    pub day: Vec<RangeInclusive<u8>>,
+
+    /// Run all the days
+    #[arg(short, long)]
+    pub all: bool,
+
+    /// Only run one part of the selected days
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    pub part: Option<u8>,
+
+    #[command(flatten)]
+    pub inputs: InputArgs,
+}
+
+// Where the inputs are read from, the same for every command
+#[derive(Args, Debug)]
+pub struct InputArgs {
+    /// Read the input of the day from this file instead of the inputs
+    /// directory, or from stdin with -
+    #[arg(short, long, value_name = "PATH")]
+    pub input: Option<PathBuf>,
+
+    /// Directory containing the day_NN.txt inputs
+    #[arg(
+        long,
+        value_name = "DIRECTORY",
+        env = "AOC_INPUTS_DIR",
+        default_value = DEFAULT_INPUTS_DIRECTORY
+    )]
+    pub inputs_dir: PathBuf,
+}
+
+impl RunArgs {
+    // Through clap, so the inputs directory comes from AOC_INPUTS_DIR like with `aoc run --all`
+    pub fn all() -> Self {
+        return match Cli::parse_from(["aoc", "run", "--all"]).command {
+            Some(Command::Run(run)) => run,
+            _ => unreachable!(),
+        };
+    }
+}
+
+impl Selection {
+    // Sorted and deduplicated list of the selected days
+    pub fn selected_days(&self) -> Vec<u8> {
+        let mut days: Vec<u8> = if self.all {
+            (1..=25).collect()
+        } else {
+            self.day.iter().cloned().flatten().collect()
+        };
+        days.sort_unstable();
+        days.dedup();
+        return days;
+    }
+
+    pub fn selected_parts(&self) -> Vec<Part> {
+        return match self.part {
+            Some(1) => vec![Part::One],
+            Some(2) => vec![Part::Two],
+            _ => Part::ALL.to_vec(),
+        };
+    }
+}
+
+fn parse_fps(fps: &str) -> Result<f64, String> {
+    let fps = fps
+        .trim()
+        .parse::<f64>()
+        .map_err(|_| format!("`{}` is not a number", fps))?;
+    if !(0.5..=120.0).contains(&fps) {
+        return Err(format!("{} is not between 0.5 and 120", fps));
+    }
+    return Ok(fps);
+}
+
+fn parse_window(window: &str) -> Result<usize, String> {
+    return match window.trim().parse::<usize>() {
+        Ok(0) => Err(String::from("a window has at least one depth")),
+        Ok(window) => Ok(window),
+        Err(_) => Err(format!("`{}` is not a window size", window)),
+    };
+}
+
+fn parse_day(day: &str) -> Result<u8, String> {
+    return parse_day_up_to(day, 25);
+}
+
+// The exclusive end of a range goes one past the last day
+fn parse_day_up_to(day: &str, last: u8) -> Result<u8, String> {
+    let day = day
+        .trim()
+        .parse::<u8>()
+        .map_err(|_| format!("`{}` is not a day number", day))?;
+    if !(1..=last).contains(&day) {
+        return Err(format!("day {} is not between 1 and {}", day, last));
+    }
+    return Ok(day);
+}
+
+fn parse_day_range(range: &str) -> Result<RangeInclusive<u8>, String> {
+    if let Some((start, end)) = range.split_once("..=") {
+        let start = parse_day(start)?;
+        let end = parse_day(end)?;
+        if end < start {
+            return Err(format!("the range `{}` is empty", range));
+        }
+        return Ok(start..=end);
+    }
+    if let Some((start, end)) = range.split_once("..") {
+        let start = parse_day(start)?;
+        let end = parse_day_up_to(end, 26)?;
+        if end <= start {
+            return Err(format!("the range `{}` is empty", range));
+        }
+        return Ok(start..=end - 1);
+    }
+    let day = parse_day(range)?;
+    return Ok(day..=day);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use advent_of_code_2021_rust::heatmap;
+
+    fn parse_run(args: &[&str]) -> Result<Selection, clap::Error> {
+        let cli = Cli::try_parse_from([&["aoc", "run"], args].concat())?;
+        match cli.command {
+            Some(Command::Run(run)) => return Ok(run.selection),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_parse_day_range() {
+        assert_eq!(parse_day_range("9"), Ok(9..=9));
+        assert_eq!(parse_day_range("3..7"), Ok(3..=6));
+        assert_eq!(parse_day_range("3..=7"), Ok(3..=7));
+        assert_eq!(parse_day_range("3..26"), Ok(3..=25));
+        assert_eq!(parse_day_range("3..=3"), Ok(3..=3));
+        assert!(parse_day_range("7..3").is_err());
+        assert!(parse_day_range("7..=3").is_err());
+        assert!(parse_day_range("3..27").is_err());
+        assert!(parse_day_range("3..=26").is_err());
+        assert!(parse_day_range("0").is_err());
+        assert!(parse_day_range("26").is_err());
+        assert!(parse_day_range("nine").is_err());
+    }
+
+    #[test]
+    fn test_selected_days() {
+        let run = parse_run(&["--day", "9", "--part", "2"]).unwrap();
+        assert_eq!(run.selected_days(), vec![9]);
+        assert_eq!(run.selected_parts(), vec![Part::Two]);
+
+        let run = parse_run(&["--day", "3..5,4", "--day", "1"]).unwrap();
+        assert_eq!(run.selected_days(), vec![1, 3, 4]);
+        assert_eq!(run.selected_parts(), vec![Part::One, Part::Two]);
+
+        let run = parse_run(&["--all"]).unwrap();
+        assert_eq!(run.selected_days().len(), 25);
+    }
+
+    #[test]
+    fn test_input_arguments() {
+        let run = parse_run(&["--day", "1", "--input", "-"]).unwrap();
+        assert_eq!(run.inputs.input, Some(PathBuf::from("-")));
+
+        let run = parse_run(&["--day", "1", "--inputs-dir", "elsewhere"]).unwrap();
+        assert_eq!(run.inputs.input, None);
+        assert_eq!(run.inputs.inputs_dir, PathBuf::from("elsewhere"));
+
+        // The same arguments for the commands of a single day
+        for command in ["visualize", "heatmap -o vents.ppm", "sonar", "trajectory"] {
+            let args = [&["aoc"], &command.split(' ').collect::<Vec<&str>>()[..]].concat();
+            let with_input = [&args[..], &["-i", "-", "--inputs-dir", "elsewhere"]].concat();
+            let inputs = match Cli::try_parse_from(with_input).unwrap().command {
+                Some(Command::Visualize(args)) => args.inputs,
+                Some(Command::Heatmap(args)) => args.inputs,
+                Some(Command::Sonar(args)) => args.inputs,
+                Some(Command::Trajectory(args)) => args.inputs,
+                _ => unreachable!(),
+            };
+            assert_eq!(inputs.input, Some(PathBuf::from("-")));
+            assert_eq!(inputs.inputs_dir, PathBuf::from("elsewhere"));
+        }
+    }
+
+    #[test]
+    fn test_format_argument() {
+        let parse = |args: &[&str]| -> Result<Format, clap::Error> {
+            let cli = Cli::try_parse_from([&["aoc", "run", "--all"], args].concat())?;
+            match cli.command {
+                Some(Command::Run(run)) => return Ok(run.format),
+                _ => unreachable!(),
+            }
+        };
+        assert_eq!(parse(&[]).unwrap(), Format::Text);
+        assert_eq!(parse(&["--format", "json"]).unwrap(), Format::Json);
+        assert_eq!(parse(&["-f", "csv"]).unwrap(), Format::Csv);
+        assert!(parse(&["--format", "xml"]).is_err());
+    }
+
+    #[test]
+    fn test_parallel_argument() {
+        let cli = Cli::try_parse_from(["aoc", "run", "--all", "--parallel"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Run(run)) if run.parallel));
+        assert!(!RunArgs::all().parallel);
+    }
+
+    #[test]
+    fn test_default_run() {
+        // No other test depends on it, they give --inputs-dir when they check it
+        std::env::set_var("AOC_INPUTS_DIR", "elsewhere");
+        let run = RunArgs::all();
+        assert!(run.selection.all);
+        assert_eq!(run.format, Format::Text);
+        assert_eq!(run.selection.inputs.inputs_dir, PathBuf::from("elsewhere"));
+
+        std::env::remove_var("AOC_INPUTS_DIR");
+        assert_eq!(
+            RunArgs::all().selection.inputs.inputs_dir,
+            PathBuf::from("inputs")
+        );
+    }
+
+    #[test]
+    fn test_bench_arguments() {
+        let cli = Cli::try_parse_from(["aoc", "bench", "--all", "-n", "10"]).unwrap();
+        match cli.command {
+            Some(Command::Bench(bench)) => {
+                assert!(bench.selection.all);
+                assert_eq!(bench.iterations, 10);
+                assert_eq!(bench.baseline, None);
+            }
+            _ => unreachable!(),
+        }
+        assert!(Cli::try_parse_from(["aoc", "bench"]).is_err());
+    }
+
+    #[test]
+    fn test_verify_arguments() {
+        let cli = Cli::try_parse_from(["aoc", "verify", "--day", "4"]).unwrap();
+        match cli.command {
+            Some(Command::Verify(verify)) => {
+                assert_eq!(verify.selection.selected_days(), vec![4]);
+                assert_eq!(verify.answers, PathBuf::from("answers.toml"));
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_visualize_arguments() {
+        let cli = Cli::try_parse_from(["aoc", "visualize", "--fps", "30", "--wrap"]).unwrap();
+        match cli.command {
+            Some(Command::Visualize(visualize)) => {
+                assert_eq!(visualize.fps, 30.0);
+                assert!(visualize.wrap);
+                assert!(!visualize.paused);
+                assert_eq!(visualize.inputs.input, None);
+            }
+            _ => unreachable!(),
+        }
+        assert!(Cli::try_parse_from(["aoc", "visualize", "--fps", "0"]).is_err());
+        assert!(Cli::try_parse_from(["aoc", "visualize", "--fps", "fast"]).is_err());
+    }
+
+    #[test]
+    fn test_heatmap_arguments() {
+        let cli = Cli::try_parse_from(["aoc", "heatmap", "-o", "vents.ppm", "--layer", "diagonal"])
+            .unwrap();
+        match cli.command {
+            Some(Command::Heatmap(heatmap)) => {
+                assert_eq!(heatmap.output, PathBuf::from("vents.ppm"));
+                assert_eq!(day_05::Layer::from(heatmap.layer), day_05::Layer::Diagonal);
+            }
+            _ => unreachable!(),
+        }
+        assert!(Cli::try_parse_from(["aoc", "heatmap"]).is_err());
+
+        let help = Cli::try_parse_from(["aoc", "heatmap", "--help"]).unwrap_err();
+        let size = format!("{}x{}", heatmap::MAX_SIZE, heatmap::MAX_SIZE);
+        assert!(help.to_string().contains(&size));
+    }
+
+    #[test]
+    fn test_sonar_arguments() {
+        let sonar = |args: &[&str]| -> Result<SonarArgs, clap::Error> {
+            return match Cli::try_parse_from(args)?.command {
+                Some(Command::Sonar(sonar)) => Ok(sonar),
+                _ => unreachable!(),
+            };
+        };
+        let args = sonar(&["aoc", "sonar"]).unwrap();
+        assert_eq!((args.windows, args.jumps), (vec![1, 3], 3));
+        let args = sonar(&["aoc", "sonar", "-w", "2,5", "--window", "10", "-j", "0"]).unwrap();
+        assert_eq!((args.windows, args.jumps), (vec![2, 5, 10], 0));
+        assert!(sonar(&["aoc", "sonar", "-w", "0"]).is_err());
+        assert!(sonar(&["aoc", "sonar", "-w", "three"]).is_err());
+    }
+
+    #[test]
+    fn test_trajectory_arguments() {
+        let cli = Cli::try_parse_from(["aoc", "trajectory", "--svg", "profile.svg"]).unwrap();
+        match cli.command {
+            Some(Command::Trajectory(trajectory)) => {
+                assert_eq!(trajectory.svg, Some(PathBuf::from("profile.svg")));
+                assert_eq!(trajectory.csv, None);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        assert!(parse_run(&[]).is_err());
+        assert!(parse_run(&["--day", "3", "--part", "3"]).is_err());
+        assert!(parse_run(&["--day", "3", "--all"]).is_err());
+    }
+}
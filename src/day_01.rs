@@ -1,33 +1,313 @@
 // use rayon::prelude::*;
 
-pub fn string_to_vec_of_ints(input: &str) -> Vec<i64> {
-    return input.split_whitespace()
-        .map(|s| s.parse::<i64>().expect("Could not parse number"))
-        .collect();
-}
-
-fn number_of_increases(numbers: &Vec<i64>) -> i64 {
-    let mut number_of_increases = 0;
-    for i in 0..numbers.len() - 1 {
-        if numbers[i] < numbers[i + 1] {
-            number_of_increases += 1;
-        }
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::io::{self, BufRead};
+
+use serde::Serialize;
+
+use crate::error::{parse_token, Error, ParseError};
+use crate::solution::Solution;
+
+// The depths of a line, there can be several separated by spaces
+fn parse_line(line_index: usize, line: &str, depths: &mut Vec<i64>) -> Result<(), ParseError> {
+  for token in line.split_whitespace() {
+    depths.push(parse_token(Day01::DAY, line_index, line, token, "depth")?);
+  }
+  return Ok(());
+}
+
+pub fn string_to_vec_of_ints(input: &str) -> Result<Vec<i64>, ParseError> {
+    let mut numbers = vec![];
+    for (line_index, line) in input.lines().enumerate() {
+        parse_line(line_index, line, &mut numbers)?;
     }
-    return number_of_increases;
+    return Ok(numbers);
 }
 
-pub fn day_1_part_1(data: &str) -> i64 {
-  return number_of_increases(&string_to_vec_of_ints(data));
+/**
+ * The depths of a reader, parsed one line at a time so the input can be
+ * larger than the memory. Parse errors are `InvalidData` errors wrapping
+ * the `ParseError`.
+ */
+pub struct Depths<R> {
+  reader: R,
+  line: String,
+  line_index: usize,
+  // The depths of the current line that were not returned yet
+  pending: Vec<i64>,
+  next_pending: usize,
 }
 
-pub fn day_1_part_2(data: &str) -> i64 {
-  let data = string_to_vec_of_ints(data);
-  let mut sliding_window : Vec<i64> = vec![0; data.len() - 2];
-  for i in 0..data.len() - 2 {
-    sliding_window[i] = data[i] + data[i + 1] + data[i + 2];
+pub fn read_depths<R: BufRead>(reader: R) -> Depths<R> {
+  return Depths {
+    reader,
+    line: String::new(),
+    line_index: 0,
+    pending: vec![],
+    next_pending: 0,
+  };
+}
+
+impl<R: BufRead> Iterator for Depths<R> {
+  type Item = io::Result<i64>;
+
+  fn next(&mut self) -> Option<io::Result<i64>> {
+    while self.next_pending == self.pending.len() {
+      self.line.clear();
+      match self.reader.read_line(&mut self.line) {
+        Ok(0) => return None,
+        Ok(_) => {}
+        Err(error) => return Some(Err(error)),
+      }
+      // Like str::lines
+      let line = self.line.strip_suffix('\n').unwrap_or(&self.line);
+      let line = line.strip_suffix('\r').unwrap_or(line);
+
+      self.pending.clear();
+      self.next_pending = 0;
+      let parsed = parse_line(self.line_index, line, &mut self.pending);
+      self.line_index += 1;
+      if let Err(error) = parsed {
+        self.pending.clear();
+        return Some(Err(io::Error::new(io::ErrorKind::InvalidData, error)));
+      }
+    }
+    self.next_pending += 1;
+    return Some(Ok(self.pending[self.next_pending - 1]));
+  }
+}
+
+/**
+ * The last `size` depths, in a ring buffer.
+ *
+ * Two consecutive windows share all their depths but the first of the one
+ * and the last of the other, so the sum changes by the new depth minus the
+ * one leaving the window. The sums are never computed and can't overflow.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Window {
+  depths: Vec<i64>,
+  size: usize,
+  // The depth that leaves the window next
+  oldest: usize,
+}
+
+impl Window {
+  // Panics if the window is empty
+  fn new(size: usize) -> Self {
+    assert!(size > 0, "the window needs at least one depth");
+    return Window {
+      depths: Vec::with_capacity(size),
+      size,
+      oldest: 0,
+    };
+  }
+
+  // The depth that left the window, None while the first window is not full
+  fn push(&mut self, depth: i64) -> Option<i64> {
+    if self.depths.len() < self.size {
+      self.depths.push(depth);
+      return None;
+    }
+    let left = std::mem::replace(&mut self.depths[self.oldest], depth);
+    self.oldest = (self.oldest + 1) % self.size;
+    return Some(left);
+  }
+}
+
+// Counts the increases of the sums of `window` consecutive depths, with only
+// the last `window` depths in memory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncreaseCounter {
+  window: Window,
+  increases: i64,
+}
+
+impl IncreaseCounter {
+  // Panics if the window is empty
+  pub fn new(window: usize) -> Self {
+    return IncreaseCounter {
+      window: Window::new(window),
+      increases: 0,
+    };
+  }
+
+  pub fn push(&mut self, depth: i64) {
+    if let Some(left) = self.window.push(depth) {
+      if depth > left {
+        self.increases += 1;
+      }
+    }
+  }
+
+  pub fn increases(&self) -> i64 {
+    return self.increases;
+  }
+}
+
+pub fn count_increases(depths: impl IntoIterator<Item = i64>, window: usize) -> i64 {
+  let mut counter = IncreaseCounter::new(window);
+  for depth in depths {
+    counter.push(depth);
+  }
+  return counter.increases();
+}
+
+// Same thing from a reader, like a file or stdin
+pub fn count_increases_in(reader: impl BufRead, window: usize) -> io::Result<i64> {
+  let mut counter = IncreaseCounter::new(window);
+  for depth in read_depths(reader) {
+    counter.push(depth?);
+  }
+  return Ok(counter.increases());
+}
+
+// A change between the sum of a window and the one of the window before it,
+// the first window is 0
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Jump {
+  pub window: u64,
+  // Between i64::MIN and i64::MAX, the change doesn't fit in an i64
+  pub change: i128,
+}
+
+// Consecutive increases, from the window start to start + length
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Streak {
+  pub start: u64,
+  pub length: u64,
+}
+
+/**
+ * What the sums of `window` consecutive depths do, beyond the number of
+ * increases of the puzzle. The largest jumps are the largest changes either
+ * way, from the largest, and in the order of the windows for equal ones.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SonarReport {
+  pub window: usize,
+  pub depths: u64,
+  pub increases: u64,
+  pub decreases: u64,
+  pub flat: u64,
+  // None without any increase, the first one of the longest ones otherwise
+  pub longest_increasing_run: Option<Streak>,
+  pub largest_jumps: Vec<Jump>,
+}
+
+/**
+ * Builds a `SonarReport` one depth at a time, in O(window + jumps) memory
+ * like the `IncreaseCounter`.
+ */
+#[derive(Debug, Clone)]
+pub struct SonarAnalyzer {
+  window: Window,
+  jumps: usize,
+  report: SonarReport,
+  current_run: Streak,
+  // The largest jumps so far, the smallest of them on top to be replaced
+  largest_jumps: BinaryHeap<Reverse<(u128, Reverse<u64>, i128)>>,
+}
+
+impl SonarAnalyzer {
+  // Keeps the `jumps` largest jumps, panics if the window is empty
+  pub fn new(window: usize, jumps: usize) -> Self {
+    return SonarAnalyzer {
+      window: Window::new(window),
+      jumps,
+      report: SonarReport {
+        window,
+        depths: 0,
+        increases: 0,
+        decreases: 0,
+        flat: 0,
+        longest_increasing_run: None,
+        largest_jumps: vec![],
+      },
+      current_run: Streak { start: 0, length: 0 },
+      largest_jumps: BinaryHeap::with_capacity(jumps + 1),
+    };
+  }
+
+  pub fn push(&mut self, depth: i64) {
+    self.report.depths += 1;
+    let left = match self.window.push(depth) {
+      Some(left) => left,
+      None => return,
+    };
+    // The window that was just completed
+    let window = self.report.depths - self.window.size as u64;
+    let change = i128::from(depth) - i128::from(left);
+
+    if change > 0 {
+      self.report.increases += 1;
+      if self.current_run.length == 0 {
+        self.current_run.start = window - 1;
+      }
+      self.current_run.length += 1;
+      let longest = self.report.longest_increasing_run.map_or(0, |run| run.length);
+      if self.current_run.length > longest {
+        self.report.longest_increasing_run = Some(self.current_run);
+      }
+    } else {
+      if change < 0 {
+        self.report.decreases += 1;
+      } else {
+        self.report.flat += 1;
+      }
+      self.current_run.length = 0;
+    }
+
+    if self.jumps > 0 {
+      self.largest_jumps.push(Reverse((change.unsigned_abs(), Reverse(window), change)));
+      if self.largest_jumps.len() > self.jumps {
+        self.largest_jumps.pop();
+      }
+    }
+  }
+
+  pub fn report(&self) -> SonarReport {
+    let mut report = self.report.clone();
+    // From the largest, into_sorted_vec is from the smallest of the Reverse
+    report.largest_jumps = self
+      .largest_jumps
+      .clone()
+      .into_sorted_vec()
+      .into_iter()
+      .map(|Reverse((_, Reverse(window), change))| Jump { window, change })
+      .collect();
+    return report;
+  }
+}
+
+pub fn sonar_report(depths: impl IntoIterator<Item = i64>, window: usize, jumps: usize) -> SonarReport {
+  let mut analyzer = SonarAnalyzer::new(window, jumps);
+  for depth in depths {
+    analyzer.push(depth);
+  }
+  return analyzer.report();
+}
+
+pub struct Day01;
+
+impl Solution for Day01 {
+  const DAY: u8 = 1;
+  const TITLE: &'static str = "Sonar Sweep";
+
+  type Input<'a> = Vec<i64>;
+
+  fn parse(data: &str) -> Result<Vec<i64>, ParseError> {
+    return string_to_vec_of_ints(data);
+  }
+
+  fn part_1(data: &Vec<i64>) -> Result<i64, Error> {
+    return Ok(count_increases(data.iter().copied(), 1));
+  }
+
+  fn part_2(data: &Vec<i64>) -> Result<i64, Error> {
+    return Ok(count_increases(data.iter().copied(), 3));
   }
-  
-  return number_of_increases(&sliding_window);
 }
 
 #[cfg(test)]
@@ -47,11 +327,81 @@ mod tests {
 
   #[test]
   fn test_day_1_part_1() {
-    assert_eq!(day_1_part_1(EXAMPLE), 7);
+    assert_eq!(Day01::solve_part_1(EXAMPLE), Ok(7));
   }
  
   #[test]
   fn test_day_1_part_2() {
-    assert_eq!(day_1_part_2(EXAMPLE), 5);
+    assert_eq!(Day01::solve_part_2(EXAMPLE), Ok(5));
+  }
+
+  #[test]
+  fn test_parse_error() {
+    assert_eq!(
+      string_to_vec_of_ints("199\n2OO"),
+      Err(ParseError::new(1, 2, 1, "2OO", "invalid depth"))
+    );
+  }
+
+  #[test]
+  fn test_sonar_report() {
+    let depths = string_to_vec_of_ints(EXAMPLE).unwrap();
+    let report = sonar_report(depths.iter().copied(), 1, 3);
+    assert_eq!(
+      (report.depths, report.increases, report.decreases, report.flat),
+      (10, 7, 2, 0)
+    );
+    // There are two runs of 3 increases
+    assert_eq!(report.longest_increasing_run, Some(Streak { start: 0, length: 3 }));
+    assert_eq!(
+      report.largest_jumps,
+      [
+        Jump { window: 6, change: 33 },
+        Jump { window: 7, change: 29 },
+        Jump { window: 4, change: -10 }
+      ]
+    );
+
+    // 607, 618, 618, 617, 647, 716, 769, 792
+    let report = sonar_report(depths.iter().copied(), 3, 2);
+    assert_eq!((report.increases, report.decreases, report.flat), (5, 1, 1));
+    assert_eq!(report.longest_increasing_run, Some(Streak { start: 3, length: 4 }));
+    assert_eq!(
+      report.largest_jumps,
+      [Jump { window: 5, change: 69 }, Jump { window: 6, change: 53 }]
+    );
+
+    let report = sonar_report([5, 5, i64::MIN, i64::MAX], 1, 1);
+    assert_eq!(report.longest_increasing_run, Some(Streak { start: 2, length: 1 }));
+    assert_eq!(report.largest_jumps[0].change, u64::MAX as i128);
+    let report = sonar_report([3, 2], 5, 1);
+    assert_eq!((report.depths, report.increases, report.longest_increasing_run), (2, 0, None));
+    assert!(report.largest_jumps.is_empty());
+  }
+
+  #[test]
+  fn test_streaming() {
+    for window in 1..=4 {
+      assert_eq!(
+        count_increases_in(EXAMPLE.as_bytes(), window).unwrap(),
+        count_increases(string_to_vec_of_ints(EXAMPLE).unwrap(), window)
+      );
+    }
+    assert_eq!(count_increases_in(EXAMPLE.as_bytes(), 3).unwrap(), 5);
+    // Too short for a single comparison
+    assert_eq!(count_increases([1, 2, 3], 3), 0);
+    assert_eq!(count_increases([], 1), 0);
+    // The sums would overflow
+    assert_eq!(count_increases([i64::MAX, 0, i64::MAX, 1], 2), 1);
+
+    let depths = read_depths("199 200\r\n\n208\n".as_bytes());
+    assert_eq!(depths.map(Result::unwrap).collect::<Vec<i64>>(), [199, 200, 208]);
+
+    let error = count_increases_in("199\n200\n2O8\n".as_bytes(), 1).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(
+      error.into_inner().unwrap().downcast::<ParseError>().unwrap(),
+      Box::new(ParseError::new(1, 3, 1, "2O8", "invalid depth"))
+    );
   }
 }
@@ -1,102 +1,434 @@
 // Totally unecessary but fun
 use rayon::prelude::*;
 
+use crate::error::{parse_token, Error, ParseError};
+use crate::solution::Solution;
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Direction {
     Up,
     Down,
     Forward,
+    Back,
 }
 
-pub struct Instruction {
-    direction: Direction,
-    units: i64,
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Instruction {
+    Move { direction: Direction, units: i64 },
+    // Back to the starting point, and to an aim of 0
+    Reset,
 }
 
+// The aim is always 0 with the plain movement
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
 pub struct Position {
-    horizontal: i64,
-    depth: i64,
+    pub horizontal: i64,
+    pub depth: i64,
+    pub aim: i64,
 }
 
-pub fn parse_submarine_instructions(input: &str) -> Vec<Instruction> {
+// Parse "direction units", or "reset" without units
+fn parse_command(line_index: usize, line: &str, command: &str) -> Result<Instruction, ParseError> {
+    let mut tokens = command.split_whitespace();
+    let direction = match tokens.next() {
+        Some("up") => Direction::Up,
+        Some("down") => Direction::Down,
+        Some("forward") => Direction::Forward,
+        Some("back") => Direction::Back,
+        Some("reset") => {
+            if let Some(token) = tokens.next() {
+                return Err(ParseError::at_token(
+                    Day02::DAY,
+                    line_index,
+                    line,
+                    token,
+                    "unexpected token",
+                ));
+            }
+            return Ok(Instruction::Reset);
+        }
+        Some(token) => {
+            return Err(ParseError::at_token(
+                Day02::DAY,
+                line_index,
+                line,
+                token,
+                "unknown direction",
+            ))
+        }
+        None => {
+            return Err(ParseError::at_end_of_line(
+                Day02::DAY,
+                line_index,
+                line,
+                "expected a direction",
+            ))
+        }
+    };
+    let units = match tokens.next() {
+        Some(token) => parse_token(Day02::DAY, line_index, line, token, "units")?,
+        None => {
+            return Err(ParseError::at_end_of_line(
+                Day02::DAY,
+                line_index,
+                line,
+                "expected a number of units",
+            ))
+        }
+    };
+    if let Some(token) = tokens.next() {
+        return Err(ParseError::at_token(
+            Day02::DAY,
+            line_index,
+            line,
+            token,
+            "unexpected token",
+        ));
+    }
+    return Ok(Instruction::Move { direction, units });
+}
+
+// Everything after a # is a comment, None for the lines without an instruction
+fn parse_instruction(line_index: usize, line: &str) -> Result<Option<Instruction>, ParseError> {
+    let command = match line.split_once('#') {
+        Some((command, _comment)) => command,
+        None => line,
+    };
+    if command.trim().is_empty() {
+        return Ok(None);
+    }
+    return parse_command(line_index, line, command).map(Some);
+}
+
+pub fn parse_submarine_instructions(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    // The lines are collected first to keep their numbers for the errors
+    let lines = input.lines().collect::<Vec<&str>>();
+    let instructions = lines
+        .par_iter()
+        .enumerate()
+        .filter_map(|(line_index, line)| parse_instruction(line_index, line).transpose())
+        .collect::<Result<Vec<Instruction>, ParseError>>();
+    // Rayon returns the error of any line, the sequential parser stops at the first one
+    return instructions.or_else(|_| parse_submarine_instructions_sequential(input));
+}
+
+// Same without rayon, to compare them in the benches
+pub fn parse_submarine_instructions_sequential(
+    input: &str,
+) -> Result<Vec<Instruction>, ParseError> {
     return input
-        .par_split(|c| c == '\n')
-        .map(|s| {
-            let instruction = s.split_whitespace().collect::<Vec<&str>>();
-            let direction = match instruction[0] {
-                "up" => Direction::Up,
-                "down" => Direction::Down,
-                "forward" => Direction::Forward,
-                _ => panic!("Unknown direction"),
-            };
-            let units = instruction[1]
-                .parse::<i64>()
-                .expect("Could not parse units number");
-            return Instruction { direction, units };
-        })
+        .lines()
+        .enumerate()
+        .filter_map(|(line_index, line)| parse_instruction(line_index, line).transpose())
         .collect();
 }
 
-pub fn day_2_part_1(data: &str) -> i64 {
-    let instructions = parse_submarine_instructions(data);
-    let final_position = instructions
-        .par_iter()
-        .fold(
-            || Position {
-                horizontal: 0,
-                depth: 0,
+pub struct Day02;
+
+impl Solution for Day02 {
+    const DAY: u8 = 2;
+    const TITLE: &'static str = "Dive!";
+
+    type Input<'a> = Vec<Instruction>;
+
+    fn parse(data: &str) -> Result<Vec<Instruction>, ParseError> {
+        return parse_submarine_instructions(data);
+    }
+
+    fn part_1(instructions: &Vec<Instruction>) -> Result<i64, Error> {
+        return Ok(final_position_product(instructions));
+    }
+
+    fn part_2(instructions: &Vec<Instruction>) -> Result<i64, Error> {
+        return Ok(final_position_with_aim_product(instructions));
+    }
+}
+
+/**
+ * What instructions do to any position, for both movements:
+ *
+ * - horizontal' = horizontal + `horizontal`
+ * - depth' = depth + `depth` + `depth_per_aim` * aim
+ * - aim' = aim + `aim`
+ *
+ * or the same from the starting point when there is a reset. Doing a
+ * transform and then another one is a transform too, and it doesn't matter
+ * which consecutive ones are combined first, so long lists of instructions
+ * can be split between threads.
+ */
+// The default one does nothing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Transform {
+    pub reset: bool,
+    pub horizontal: i64,
+    pub depth: i64,
+    pub aim: i64,
+    // The horizontal moves, each one changes the depth by the aim at that time
+    pub depth_per_aim: i64,
+}
+
+impl Transform {
+    pub const RESET: Transform = Transform {
+        reset: true,
+        horizontal: 0,
+        depth: 0,
+        aim: 0,
+        depth_per_aim: 0,
+    };
+
+    pub fn apply(self, position: Position) -> Position {
+        let from = if self.reset {
+            Position::default()
+        } else {
+            position
+        };
+        return Position {
+            horizontal: from.horizontal + self.horizontal,
+            depth: from.depth + self.depth + self.depth_per_aim * from.aim,
+            aim: from.aim + self.aim,
+        };
+    }
+
+    // self and then next
+    pub fn then(self, next: Transform) -> Transform {
+        if next.reset {
+            return next;
+        }
+        return Transform {
+            reset: self.reset,
+            horizontal: self.horizontal + next.horizontal,
+            // The horizontal moves of next are done with the aim changed by self
+            depth: self.depth + next.depth + next.depth_per_aim * self.aim,
+            aim: self.aim + next.aim,
+            depth_per_aim: self.depth_per_aim + next.depth_per_aim,
+        };
+    }
+}
+
+/**
+ * How the submarine moves, which changes between the parts of the puzzle.
+ * The resets are the same for all of them.
+ */
+pub trait Movement {
+    fn transform(&self, direction: Direction, units: i64) -> Transform;
+
+    fn apply(&self, position: Position, direction: Direction, units: i64) -> Position {
+        return self.transform(direction, units).apply(position);
+    }
+
+    fn instruction_transform(&self, instruction: &Instruction) -> Transform {
+        return match *instruction {
+            Instruction::Move { direction, units } => self.transform(direction, units),
+            Instruction::Reset => Transform::RESET,
+        };
+    }
+}
+
+// Part 1, up and down change the depth directly
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Plain;
+
+impl Movement for Plain {
+    fn transform(&self, direction: Direction, units: i64) -> Transform {
+        return match direction {
+            Direction::Up => Transform {
+                depth: -units,
+                ..Transform::default()
             },
-            |pos, instruction| match instruction.direction {
-                Direction::Up => Position {
-                    horizontal: pos.horizontal,
-                    depth: pos.depth - instruction.units,
-                },
-                Direction::Down => Position {
-                    horizontal: pos.horizontal,
-                    depth: pos.depth + instruction.units,
-                },
-                Direction::Forward => Position {
-                    horizontal: pos.horizontal + instruction.units,
-                    depth: pos.depth,
-                },
+            Direction::Down => Transform {
+                depth: units,
+                ..Transform::default()
             },
-        )
-        .reduce(
-            || Position {
-                horizontal: 0,
-                depth: 0,
+            Direction::Forward => Transform {
+                horizontal: units,
+                ..Transform::default()
             },
-            |a, b| Position {
-                horizontal: a.horizontal + b.horizontal,
-                depth: a.depth + b.depth,
+            Direction::Back => Transform {
+                horizontal: -units,
+                ..Transform::default()
             },
-        );
+        };
+    }
+}
 
-    return final_position.horizontal * final_position.depth;
+// Part 2, up and down change the aim, and the depth changes when moving
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WithAim;
+
+impl Movement for WithAim {
+    fn transform(&self, direction: Direction, units: i64) -> Transform {
+        return match direction {
+            Direction::Up => Transform {
+                aim: -units,
+                ..Transform::default()
+            },
+            Direction::Down => Transform {
+                aim: units,
+                ..Transform::default()
+            },
+            Direction::Forward => Transform {
+                horizontal: units,
+                depth_per_aim: units,
+                ..Transform::default()
+            },
+            // Undoes a forward with the same aim
+            Direction::Back => Transform {
+                horizontal: -units,
+                depth_per_aim: -units,
+                ..Transform::default()
+            },
+        };
+    }
 }
 
-pub fn day_2_part_2(data: &str) -> i64 {
-    let instructions = parse_submarine_instructions(data);
-    let mut horizontal: i64 = 0;
-    let mut depth: i64 = 0;
-    let mut aim: i64 = 0;
+/**
+ * The submarine, starting at 0 and moving with the instructions one by one.
+ * `trace` gives every position on the way, `run` only the last one.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Submarine<M: Movement> {
+    movement: M,
+    position: Position,
+}
 
-    for instruction in instructions {
-        match instruction.direction {
-            Direction::Up => aim -= instruction.units,
-            Direction::Down => aim += instruction.units,
-            Direction::Forward => {
-                horizontal += instruction.units;
-                depth += instruction.units * aim;
-            }
+impl<M: Movement> Submarine<M> {
+    pub fn new(movement: M) -> Self {
+        return Submarine {
+            movement,
+            position: Position::default(),
+        };
+    }
+
+    pub fn position(&self) -> Position {
+        return self.position;
+    }
+
+    // Returns the new position
+    pub fn execute(&mut self, instruction: &Instruction) -> Position {
+        self.position = self
+            .movement
+            .instruction_transform(instruction)
+            .apply(self.position);
+        return self.position;
+    }
+
+    // Returns the final position
+    pub fn run(&mut self, instructions: &[Instruction]) -> Position {
+        for instruction in instructions {
+            self.execute(instruction);
         }
+        return self.position;
+    }
+
+    // The position after each instruction, executed as the iterator goes
+    pub fn trace<'a>(
+        &'a mut self,
+        instructions: &'a [Instruction],
+    ) -> impl Iterator<Item = Position> + 'a {
+        return instructions
+            .iter()
+            .map(move |instruction| self.execute(instruction));
     }
-    return horizontal * depth;
+}
+
+// The starting point and then the position after each instruction
+pub fn trajectory<M: Movement>(movement: M, instructions: &[Instruction]) -> Vec<Position> {
+    let mut submarine = Submarine::new(movement);
+    let mut positions = vec![submarine.position()];
+    positions.extend(submarine.trace(instructions));
+    return positions;
+}
+
+// The transforms of the instructions combined on the rayon thread pool
+pub fn final_position_parallel<M: Movement + Sync>(
+    movement: &M,
+    instructions: &[Instruction],
+) -> Position {
+    return instructions
+        .par_iter()
+        .fold(Transform::default, |transform, instruction| {
+            transform.then(movement.instruction_transform(instruction))
+        })
+        .reduce(Transform::default, Transform::then)
+        .apply(Position::default());
+}
+
+/**
+ * Same as `trajectory`, as a parallel prefix scan: the instructions are
+ * split in chunks, the transform of every chunk is computed in parallel,
+ * which gives the position at the start of every chunk, and then the
+ * chunks are traced in parallel from there.
+ */
+pub fn trajectory_parallel<M: Movement + Sync>(
+    movement: &M,
+    instructions: &[Instruction],
+) -> Vec<Position> {
+    let mut positions = vec![Position::default(); instructions.len() + 1];
+    if instructions.is_empty() {
+        return positions;
+    }
+    // A few chunks per thread, in case some threads are busy
+    let chunk_size = instructions
+        .len()
+        .div_ceil(4 * rayon::current_num_threads())
+        .max(1);
+
+    let transforms = instructions
+        .par_chunks(chunk_size)
+        .map(|chunk| {
+            return chunk
+                .iter()
+                .fold(Transform::default(), |transform, instruction| {
+                    transform.then(movement.instruction_transform(instruction))
+                });
+        })
+        .collect::<Vec<Transform>>();
+    // Only one position per chunk, there is no need to do that in parallel
+    let mut starts = Vec::with_capacity(transforms.len());
+    let mut start = Position::default();
+    for transform in transforms {
+        starts.push(start);
+        start = transform.apply(start);
+    }
+
+    positions[1..]
+        .par_chunks_mut(chunk_size)
+        .zip(instructions.par_chunks(chunk_size))
+        .zip(starts)
+        .for_each(|((positions, instructions), start)| {
+            let mut position = start;
+            for (slot, instruction) in positions.iter_mut().zip(instructions) {
+                position = movement.instruction_transform(instruction).apply(position);
+                *slot = position;
+            }
+        });
+    return positions;
+}
+
+pub fn final_position_product(instructions: &[Instruction]) -> i64 {
+    let final_position = final_position_parallel(&Plain, instructions);
+    return final_position.horizontal * final_position.depth;
+}
+
+pub fn final_position_product_sequential(instructions: &[Instruction]) -> i64 {
+    let final_position = Submarine::new(Plain).run(instructions);
+    return final_position.horizontal * final_position.depth;
+}
+
+pub fn final_position_with_aim_product(instructions: &[Instruction]) -> i64 {
+    let final_position = final_position_parallel(&WithAim, instructions);
+    return final_position.horizontal * final_position.depth;
+}
+
+pub fn final_position_with_aim_product_sequential(instructions: &[Instruction]) -> i64 {
+    let final_position = Submarine::new(WithAim).run(instructions);
+    return final_position.horizontal * final_position.depth;
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     const EXAMPLE: &str = "forward 5
     down 5
@@ -107,11 +439,181 @@ mod tests {
 
     #[test]
     fn test_day_2_part_1() {
-        assert_eq!(day_2_part_1(EXAMPLE), 150);
+        assert_eq!(Day02::solve_part_1(EXAMPLE), Ok(150));
     }
 
     #[test]
     fn test_day_2_part_2() {
-        assert_eq!(day_2_part_2(EXAMPLE), 900);
+        assert_eq!(Day02::solve_part_2(EXAMPLE), Ok(900));
+    }
+
+    #[test]
+    fn test_sequential_variants() {
+        let instructions = parse_submarine_instructions_sequential(EXAMPLE).unwrap();
+        assert_eq!(
+            Ok(instructions.clone()),
+            parse_submarine_instructions(EXAMPLE)
+        );
+        assert_eq!(final_position_product_sequential(&instructions), 150);
+        assert_eq!(final_position_product(&instructions), 150);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Day02::parse("forward 5\nsideways 3").unwrap_err(),
+            ParseError::new(2, 2, 1, "sideways", "unknown direction")
+        );
+        assert_eq!(
+            Day02::parse("forward 5\ndown").unwrap_err(),
+            ParseError::new(2, 2, 5, "", "expected a number of units")
+        );
+        assert_eq!(
+            Day02::parse("reset 3").unwrap_err(),
+            ParseError::new(2, 1, 7, "3", "unexpected token")
+        );
+        assert_eq!(
+            Day02::parse("forward 5 # up 2 x\nback x # 3").unwrap_err(),
+            ParseError::new(2, 2, 6, "x", "invalid units")
+        );
+
+        // The first bad line, whichever one the threads see first
+        let mut data = vec!["forward 5"; 10_000];
+        data[5_000] = "down x";
+        data[9_000] = "sideways 3";
+        data.push("up");
+        assert_eq!(
+            Day02::parse(&data.join("\n")).unwrap_err(),
+            ParseError::new(2, 5_001, 6, "x", "invalid units")
+        );
+    }
+
+    #[test]
+    fn test_extra_commands() {
+        let data = "# A comment\n\nforward 5  # and another\ndown 2\nback 3\n   \nup 1";
+        let instructions = parse_submarine_instructions(data).unwrap();
+        assert_eq!(
+            instructions,
+            parse_submarine_instructions_sequential(data).unwrap()
+        );
+        assert_eq!(instructions.len(), 4);
+        assert_eq!(
+            instructions[2],
+            Instruction::Move {
+                direction: Direction::Back,
+                units: 3
+            }
+        );
+
+        let mut submarine = Submarine::new(Plain);
+        assert_eq!(
+            submarine.run(&instructions),
+            Position {
+                horizontal: 2,
+                depth: 1,
+                aim: 0
+            }
+        );
+        // Going back while aiming down goes up
+        assert_eq!(
+            Submarine::new(WithAim).run(&instructions),
+            Position {
+                horizontal: 2,
+                depth: -6,
+                aim: 1
+            }
+        );
+
+        let instructions =
+            parse_submarine_instructions("forward 5\ndown 2\nreset\nforward 1").unwrap();
+        assert_eq!(final_position_product(&instructions), 0);
+        let instructions =
+            parse_submarine_instructions("forward 5\nreset\ndown 2\nforward 1").unwrap();
+        assert_eq!(final_position_product(&instructions), 2);
+        assert_eq!(final_position_product_sequential(&instructions), 2);
+        assert_eq!(final_position_with_aim_product(&instructions), 2);
+    }
+
+    #[test]
+    fn test_trace() {
+        let instructions = Day02::parse(EXAMPLE).unwrap();
+        let mut submarine = Submarine::new(WithAim);
+        let trace = submarine.trace(&instructions).collect::<Vec<Position>>();
+        assert_eq!(trace.len(), 6);
+        assert_eq!(
+            trace[2],
+            Position {
+                horizontal: 13,
+                depth: 40,
+                aim: 5
+            }
+        );
+        assert_eq!(trace[5], submarine.position());
+        assert_eq!(
+            submarine.position().horizontal * submarine.position().depth,
+            900
+        );
+
+        let mut submarine = Submarine::new(Plain);
+        let depths = submarine
+            .trace(&instructions)
+            .map(|position| position.depth);
+        assert_eq!(depths.collect::<Vec<i64>>(), [0, 5, 5, 2, 10, 10]);
+    }
+
+    fn instruction() -> impl Strategy<Value = Instruction> {
+        let direction = prop_oneof![
+            Just(Direction::Up),
+            Just(Direction::Down),
+            Just(Direction::Forward),
+            Just(Direction::Back),
+        ];
+        // Few resets, or there would be almost nothing between them
+        return prop_oneof![
+            50 => (direction, 0..1000i64)
+                .prop_map(|(direction, units)| Instruction::Move { direction, units }),
+            1 => Just(Instruction::Reset),
+        ];
+    }
+
+    proptest! {
+        #[test]
+        fn test_parallel_is_sequential(
+            instructions in prop::collection::vec(instruction(), 0..1000)
+        ) {
+            prop_assert_eq!(
+                final_position_product(&instructions),
+                final_position_product_sequential(&instructions)
+            );
+            prop_assert_eq!(
+                final_position_with_aim_product(&instructions),
+                final_position_with_aim_product_sequential(&instructions)
+            );
+            prop_assert_eq!(
+                trajectory_parallel(&WithAim, &instructions),
+                trajectory(WithAim, &instructions)
+            );
+            prop_assert_eq!(
+                trajectory_parallel(&Plain, &instructions),
+                trajectory(Plain, &instructions)
+            );
+        }
+
+        #[test]
+        fn test_transforms_are_associative(
+            a in instruction(),
+            b in instruction(),
+            c in instruction(),
+            position in (-1000..1000i64, -1000..1000i64, -1000..1000i64)
+        ) {
+            let (a, b, c) = (
+                WithAim.instruction_transform(&a),
+                WithAim.instruction_transform(&b),
+                WithAim.instruction_transform(&c),
+            );
+            prop_assert_eq!(a.then(b).then(c), a.then(b.then(c)));
+            let position = Position { horizontal: position.0, depth: position.1, aim: position.2 };
+            prop_assert_eq!(a.then(b).apply(position), b.apply(a.apply(position)));
+        }
     }
 }
@@ -1,57 +1,181 @@
-use std::panic;
-
 // Totally unecessary and actually slower for small datasets but fun
 use rayon::prelude::*;
 
-pub type DiagnosticRawData<'a> =
-    rayon::iter::Map<rayon::str::SplitWhitespace<'a>, fn(&str) -> Vec<i64>>;
+use crate::error::{Error, ParseError};
+use crate::solution::Solution;
+
+// A set of readings, one bit per reading
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitSet {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitSet {
+    pub fn new(len: usize) -> Self {
+        return BitSet {
+            words: vec![0; len.div_ceil(64)],
+            len,
+        };
+    }
+
+    // All the bits from 0 to len - 1
+    pub fn full(len: usize) -> Self {
+        let mut set = BitSet {
+            words: vec![u64::MAX; len.div_ceil(64)],
+            len,
+        };
+        set.clear_padding();
+        return set;
+    }
+
+    // The bits after len in the last word stay at 0, so they are never counted
+    fn clear_padding(&mut self) {
+        if !self.len.is_multiple_of(64) {
+            if let Some(last) = self.words.last_mut() {
+                *last &= (1 << (self.len % 64)) - 1;
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        return self.len;
+    }
 
-pub fn parse_diagnostic(input: &str) -> DiagnosticRawData {
-    return input.par_split_whitespace().map(|binary_number| {
-        binary_number
-            .as_bytes()
+    pub fn is_empty(&self) -> bool {
+        return self.len == 0;
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        return self.words[index / 64] & (1 << (index % 64)) != 0;
+    }
+
+    pub fn set(&mut self, index: usize) {
+        self.words[index / 64] |= 1 << (index % 64);
+    }
+
+    // Grows by one bit, set or not
+    pub fn push(&mut self, bit: bool) {
+        if self.len.is_multiple_of(64) {
+            self.words.push(0);
+        }
+        self.len += 1;
+        if bit {
+            self.set(self.len - 1);
+        }
+    }
+
+    pub fn count_ones(&self) -> usize {
+        return self
+            .words
             .iter()
-            .map(|&x| i64::from(x - 48))
-            .collect::<Vec<i64>>()
-    });
+            .map(|word| word.count_ones() as usize)
+            .sum();
+    }
+
+    // The number of bits set in both, without building the intersection
+    pub fn count_common_ones(&self, other: &BitSet) -> usize {
+        return self
+            .words
+            .iter()
+            .zip(&other.words)
+            .map(|(a, b)| (a & b).count_ones() as usize)
+            .sum();
+    }
+
+    // Keeps the bits that are set in other, or the ones that are not with inverted
+    pub fn intersect(&mut self, other: &BitSet, inverted: bool) {
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word &= if inverted { !other } else { *other };
+        }
+    }
+
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        return (0..self.len).filter(|&index| self.get(index));
+    }
 }
 
-pub struct DiagnosticData<'a> {
-    pub data: DiagnosticRawData<'a>,
-    pub number_of_lines: usize,
+/**
+ * The binary numbers of the diagnostic report, packed column by column:
+ * `columns[i]` has the bit `r` set when the digit `i` of the reading `r` is
+ * a 1, from the leftmost digit. Counting the ones of a column is a popcount
+ * of n / 64 words, and the filters of part 2 only narrow down a set of the
+ * remaining readings instead of copying them. Any number of digits works.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub number_of_readings: usize,
     pub number_of_digits: usize,
-    pub half: i64,
+    columns: Vec<BitSet>,
 }
 
-pub fn compute_sum_on_ones(diagnostic: DiagnosticRawData, number_of_digits: usize) -> Vec<i64> {
-    return diagnostic.reduce(
-        || vec![0; number_of_digits],
-        |acc, x| {
-            acc.iter()
-                .zip(x.iter())
-                .map(|(a, b)| a + b)
-                .collect::<Vec<i64>>()
-        },
-    );
-}
+impl Diagnostic {
+    pub fn column(&self, index: usize) -> &BitSet {
+        return &self.columns[index];
+    }
 
-pub fn compute_sum_on_ones_2(diagnostic: &Vec<Vec<i64>>, index: usize) -> i64 {
-    return diagnostic.par_iter().map(|x| x[index]).sum();
+    // The number of ones of each column, from the leftmost
+    pub fn column_sums(&self) -> Vec<usize> {
+        return self.columns.par_iter().map(BitSet::count_ones).collect();
+    }
+
+    // Same without rayon, to check in the benches if it was worth it
+    pub fn column_sums_sequential(&self) -> Vec<usize> {
+        return self.columns.iter().map(BitSet::count_ones).collect();
+    }
+
+    // The value of a reading, None if it doesn't fit in an i64
+    pub fn value(&self, reading: usize) -> Option<i64> {
+        let mut value: i64 = 0;
+        for column in &self.columns {
+            value = value.checked_mul(2)? + i64::from(column.get(reading));
+        }
+        return Some(value);
+    }
 }
 
-pub fn compute_diagnostic_data(data: &str) -> DiagnosticData {
-    let diagnostic = parse_diagnostic(data);
-    let data = diagnostic.clone();
-    let number_of_lines = diagnostic.clone().count();
-    let number_of_digits = diagnostic.clone().max_by_key(|x| x.len()).unwrap().len();
-    let half = (number_of_lines as f64 / 2.0).ceil() as i64;
-
-    return DiagnosticData {
-        data,
-        number_of_lines,
-        number_of_digits,
-        half,
-    };
+// The binary numbers are separated by spaces or lines, and they all have the same length
+pub fn parse_diagnostic(data: &str) -> Result<Diagnostic, ParseError> {
+    let mut columns: Vec<BitSet> = vec![];
+    let mut number_of_readings = 0;
+    for (line_index, line) in data.lines().enumerate() {
+        for binary_number in line.split_whitespace() {
+            if number_of_readings == 0 {
+                columns = vec![BitSet::new(0); binary_number.len()];
+            }
+            if binary_number.len() != columns.len() {
+                return Err(ParseError::at_token(
+                    Day03::DAY,
+                    line_index,
+                    line,
+                    binary_number,
+                    format!("expected {} digits", columns.len()),
+                ));
+            }
+            for ((index, digit), column) in binary_number.char_indices().zip(&mut columns) {
+                if digit != '0' && digit != '1' {
+                    return Err(ParseError::at_token(
+                        Day03::DAY,
+                        line_index,
+                        line,
+                        &binary_number[index..index + digit.len_utf8()],
+                        "not a binary digit",
+                    ));
+                }
+                column.push(digit == '1');
+            }
+            number_of_readings += 1;
+        }
+    }
+    if number_of_readings == 0 {
+        return Err(ParseError::new(Day03::DAY, 1, 1, "", "empty diagnostic"));
+    }
+
+    return Ok(Diagnostic {
+        number_of_readings,
+        number_of_digits: columns.len(),
+        columns,
+    });
 }
 
 #[derive(Debug, PartialEq)]
@@ -60,92 +184,144 @@ pub enum CommonMode {
     Uncommon,
 }
 
+/**
+ * Which digit is the most common in a column with as many ones as zeros,
+ * the other one is then the least common. The puzzle only says it for the
+ * ratings of part 2, where the ones win.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreak {
+    PreferOne,
+    PreferZero,
+    // The readings are rejected
+    Error,
+}
+
+// Whether 1 is the most common digit, None for a tie that is an error
+fn most_common_is_one(ones: usize, count: usize, tie_break: TieBreak) -> Option<bool> {
+    if 2 * ones != count {
+        return Some(2 * ones > count);
+    }
+    return match tie_break {
+        TieBreak::PreferOne => Some(true),
+        TieBreak::PreferZero => Some(false),
+        TieBreak::Error => None,
+    };
+}
+
+/**
+ * The reading left when keeping only the most or least common digit of
+ * each column among the remaining readings, from the leftmost.
+ *
+ * A column where all the remaining readings have the same digit doesn't
+ * remove them all in the Uncommon mode, it's skipped. And when the digits
+ * run out with several readings left, they are all the same one.
+ */
 pub fn filter_diagnostic_data_per_common_bit(
-    data: &Vec<Vec<i64>>,
-    number_of_digits: usize,
+    diagnostic: &Diagnostic,
     mode: CommonMode,
-) -> i64 {
-    let mut current_index = 0;
-    let mut current_data = data.clone();
+    tie_break: TieBreak,
+) -> Result<usize, Error> {
+    let mut remaining = BitSet::full(diagnostic.number_of_readings);
+    let mut number_remaining = diagnostic.number_of_readings;
 
-    loop {
-        if current_index > number_of_digits {
-            panic!("We ran out of digits to check");
+    for (index, column) in diagnostic.columns.iter().enumerate() {
+        if number_remaining == 1 {
+            break;
+        }
+        let ones = remaining.count_common_ones(column);
+        if ones == 0 || ones == number_remaining {
+            continue;
         }
+        let most_common_is_one =
+            most_common_is_one(ones, number_remaining, tie_break).ok_or_else(|| {
+                Error::no_solution(
+                    Day03::DAY,
+                    format!(
+                        "As many ones as zeros in the column {} of the {} remaining readings",
+                        index + 1,
+                        number_remaining
+                    ),
+                )
+            })?;
+        let keep_ones = most_common_is_one == (mode == CommonMode::Common);
+        remaining.intersect(column, !keep_ones);
+        number_remaining = if keep_ones {
+            ones
+        } else {
+            number_remaining - ones
+        };
+    }
+    return Ok(remaining
+        .ones()
+        .next()
+        .expect("a reading is always remaining"));
+}
 
-        let sum_of_ones = compute_sum_on_ones_2(&current_data, current_index);
-        let half = (current_data.len() as f64 / 2.0).ceil() as i64;
+pub struct Day03;
 
-        let new_data = current_data
-            .iter()
-            .filter(|digits| {
-                let should_be_a_one = (sum_of_ones >= half) == (mode == CommonMode::Common);
-                let digit = digits[current_index];
-                if digit > 1 {
-                    panic!("We should never have a digit greater than 1");
-                }
-                return (digit == 0 && !should_be_a_one) || (digit == 1 && should_be_a_one);
-            })
-            .cloned()
-            .collect::<Vec<Vec<i64>>>();
+impl Solution for Day03 {
+    const DAY: u8 = 3;
+    const TITLE: &'static str = "Binary Diagnostic";
 
-        current_data = new_data;
-        current_index += 1;
+    type Input<'a> = Diagnostic;
 
-        let len = current_data.len();
-        if len == 1 {
-            break;
-        }
-        if len == 0 {
-            panic!("no correct digit found");
-        }
+    fn parse(data: &str) -> Result<Diagnostic, ParseError> {
+        return parse_diagnostic(data);
     }
 
-    return current_data
-        .get(0)
-        .expect("we should expect at least one element")
-        .iter()
-        .enumerate()
-        .fold(0, |acc, (index, digit)| {
-            acc + (digit * (1 << (number_of_digits - 1 - index)))
-        });
+    // The puzzle inputs have no tie for gamma and epsilon
+    fn part_1(diagnostic: &Diagnostic) -> Result<i64, Error> {
+        return power_consumption(diagnostic, TieBreak::Error);
+    }
+
+    fn part_2(diagnostic: &Diagnostic) -> Result<i64, Error> {
+        return life_support_rating(diagnostic, TieBreak::PreferOne);
+    }
 }
 
-pub fn day_3_part_1(data: &str) -> i64 {
-    let diagnostic = compute_diagnostic_data(data);
-    let sum_of_ones = compute_sum_on_ones(diagnostic.data, diagnostic.number_of_digits);
+fn too_large() -> Error {
+    return Error::no_solution(Day03::DAY, "The answer doesn't fit in 64 bits");
+}
 
+// Gamma has the most common digit of each column, epsilon the least common one
+pub fn gamma_and_epsilon(
+    diagnostic: &Diagnostic,
+    tie_break: TieBreak,
+) -> Result<(i64, i64), Error> {
     let mut gamma: i64 = 0;
     let mut epsilon: i64 = 0;
-    for (index, sum) in sum_of_ones.iter().enumerate() {
-        if *sum == diagnostic.half {
-            panic!("Undocumented behaviour : we panic");
-        }
-        if *sum >= diagnostic.half {
-            gamma += 1 << (diagnostic.number_of_digits - 1 - index);
-        } else {
-            epsilon += 1 << (diagnostic.number_of_digits - 1 - index);
-        }
+    for (index, ones) in diagnostic.column_sums().into_iter().enumerate() {
+        let most_common_is_one = most_common_is_one(ones, diagnostic.number_of_readings, tie_break)
+            .ok_or_else(|| {
+                Error::no_solution(
+                    Day03::DAY,
+                    format!("As many ones as zeros in the column {}", index + 1),
+                )
+            })?;
+        let gamma_bit = i64::from(most_common_is_one);
+        gamma = gamma.checked_mul(2).ok_or_else(too_large)? + gamma_bit;
+        epsilon = epsilon.checked_mul(2).ok_or_else(too_large)? + (1 - gamma_bit);
     }
-    return gamma * epsilon;
+    return Ok((gamma, epsilon));
+}
+
+pub fn power_consumption(diagnostic: &Diagnostic, tie_break: TieBreak) -> Result<i64, Error> {
+    let (gamma, epsilon) = gamma_and_epsilon(diagnostic, tie_break)?;
+    return gamma.checked_mul(epsilon).ok_or_else(too_large);
 }
 
-pub fn day_3_part_2(data: &str) -> i64 {
-    let diagnostic = compute_diagnostic_data(data);
-
-    let data = diagnostic.data.collect::<Vec<Vec<i64>>>();
-
-    let oxygen_generator_rating = filter_diagnostic_data_per_common_bit(
-        &data,
-        diagnostic.number_of_digits,
-        CommonMode::Common,
-    );
-    let co2_scrubber_rating = filter_diagnostic_data_per_common_bit(
-        &data,
-        diagnostic.number_of_digits,
-        CommonMode::Uncommon,
-    );
-    return oxygen_generator_rating * co2_scrubber_rating;
+pub fn life_support_rating(diagnostic: &Diagnostic, tie_break: TieBreak) -> Result<i64, Error> {
+    let oxygen_generator =
+        filter_diagnostic_data_per_common_bit(diagnostic, CommonMode::Common, tie_break)?;
+    let co2_scrubber =
+        filter_diagnostic_data_per_common_bit(diagnostic, CommonMode::Uncommon, tie_break)?;
+
+    let oxygen_generator_rating = diagnostic.value(oxygen_generator).ok_or_else(too_large)?;
+    let co2_scrubber_rating = diagnostic.value(co2_scrubber).ok_or_else(too_large)?;
+    return oxygen_generator_rating
+        .checked_mul(co2_scrubber_rating)
+        .ok_or_else(too_large);
 }
 
 #[cfg(test)]
@@ -167,11 +343,154 @@ mod tests {
 
     #[test]
     fn test_day_3_part_1() {
-        assert_eq!(day_3_part_1(EXAMPLE), 198);
+        assert_eq!(Day03::solve_part_1(EXAMPLE), Ok(198));
     }
 
     #[test]
     fn test_day_3_part_2() {
-        assert_eq!(day_3_part_2(EXAMPLE), 230);
+        assert_eq!(Day03::solve_part_2(EXAMPLE), Ok(230));
+    }
+
+    #[test]
+    fn test_sequential_variants() {
+        let diagnostic = parse_diagnostic(EXAMPLE).unwrap();
+        assert_eq!(diagnostic.column_sums(), [7, 5, 8, 7, 5]);
+        assert_eq!(
+            diagnostic.column_sums_sequential(),
+            diagnostic.column_sums()
+        );
+    }
+
+    #[test]
+    fn test_packed_readings() {
+        let diagnostic = parse_diagnostic(EXAMPLE).unwrap();
+        assert_eq!(
+            (diagnostic.number_of_readings, diagnostic.number_of_digits),
+            (12, 5)
+        );
+        assert_eq!(diagnostic.value(1), Some(0b11110));
+        assert_eq!(
+            diagnostic.column(0).ones().collect::<Vec<usize>>(),
+            [1, 2, 3, 4, 7, 8, 9]
+        );
+        let oxygen = filter_diagnostic_data_per_common_bit(
+            &diagnostic,
+            CommonMode::Common,
+            TieBreak::PreferOne,
+        );
+        assert_eq!(diagnostic.value(oxygen.unwrap()), Some(23));
+
+        // More readings than a word, and more digits too
+        let reading = |r: usize| format!("{}{:064b}", r % 2, r);
+        let data = (0..150).map(reading).collect::<Vec<String>>().join(" ");
+        let diagnostic = parse_diagnostic(&data).unwrap();
+        assert_eq!(diagnostic.number_of_digits, 65);
+        assert_eq!(diagnostic.column(0).count_ones(), 75);
+        assert_eq!(diagnostic.column(64).len(), 150);
+        assert_eq!(diagnostic.value(149), None);
+        // Three ones in every column, and no tie
+        let data = format!("{}\n{}", vec!["1".repeat(65); 3].join("\n"), "0".repeat(65));
+        assert_eq!(
+            Day03::solve_part_1(&data),
+            Err(Error::no_solution(3, "The answer doesn't fit in 64 bits"))
+        );
+
+        let mut remaining = BitSet::full(70);
+        assert_eq!(remaining.count_ones(), 70);
+        // The readings below 70 with a 0 as second bit
+        remaining.intersect(diagnostic.column(63), true);
+        assert_eq!(remaining.count_ones(), 36);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_diagnostic("00100\n11210"),
+            Err(ParseError::new(3, 2, 3, "2", "not a binary digit"))
+        );
+        assert_eq!(
+            parse_diagnostic("00100\n1110"),
+            Err(ParseError::new(3, 2, 1, "1110", "expected 5 digits"))
+        );
+        assert!(parse_diagnostic("\n").is_err());
+    }
+
+    #[test]
+    fn test_tie_break() {
+        // Two ones out of four in the first and last columns
+        let diagnostic = parse_diagnostic("110\n111\n010\n011").unwrap();
+        assert_eq!(
+            gamma_and_epsilon(&diagnostic, TieBreak::PreferOne),
+            Ok((0b111, 0b000))
+        );
+        assert_eq!(
+            gamma_and_epsilon(&diagnostic, TieBreak::PreferZero),
+            Ok((0b010, 0b101))
+        );
+        assert_eq!(
+            Day03::part_1(&diagnostic),
+            Err(Error::no_solution(
+                3,
+                "As many ones as zeros in the column 1"
+            ))
+        );
+        // A majority of one is not a tie
+        let diagnostic = parse_diagnostic("10\n10\n01").unwrap();
+        assert_eq!(
+            gamma_and_epsilon(&diagnostic, TieBreak::Error),
+            Ok((0b10, 0b01))
+        );
+
+        let diagnostic = parse_diagnostic("110\n111\n010\n011").unwrap();
+        let rating = |mode: CommonMode, tie_break: TieBreak| {
+            let reading = filter_diagnostic_data_per_common_bit(&diagnostic, mode, tie_break)?;
+            return Ok::<Option<i64>, Error>(diagnostic.value(reading));
+        };
+        assert_eq!(
+            rating(CommonMode::Common, TieBreak::PreferOne),
+            Ok(Some(0b111))
+        );
+        assert_eq!(
+            rating(CommonMode::Uncommon, TieBreak::PreferOne),
+            Ok(Some(0b010))
+        );
+        assert_eq!(
+            rating(CommonMode::Common, TieBreak::PreferZero),
+            Ok(Some(0b010))
+        );
+        assert_eq!(
+            rating(CommonMode::Uncommon, TieBreak::PreferZero),
+            Ok(Some(0b111))
+        );
+        assert_eq!(
+            rating(CommonMode::Common, TieBreak::Error),
+            Err(Error::no_solution(
+                3,
+                "As many ones as zeros in the column 1 of the 4 remaining readings"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_noisy_readings() {
+        // The same digit everywhere in the middle, and the same readings twice
+        let diagnostic = parse_diagnostic("101\n101\n111\n111\n111").unwrap();
+        assert_eq!(
+            filter_diagnostic_data_per_common_bit(
+                &diagnostic,
+                CommonMode::Uncommon,
+                TieBreak::Error
+            ),
+            Ok(0)
+        );
+        assert_eq!(
+            filter_diagnostic_data_per_common_bit(&diagnostic, CommonMode::Common, TieBreak::Error),
+            Ok(2)
+        );
+        assert_eq!(Day03::part_2(&diagnostic), Ok(0b101 * 0b111));
+
+        let diagnostic = parse_diagnostic("1").unwrap();
+        assert_eq!(Day03::part_1(&diagnostic), Ok(0));
+        assert_eq!(Day03::part_2(&diagnostic), Ok(1));
     }
 }
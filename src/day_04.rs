@@ -1,14 +1,18 @@
-use std::panic;
+use crate::error::{parse_token, Error, ParseError};
+use crate::solution::Solution;
 
-#[derive(Debug)]
+// The bingo numbers go from 0 to 127, they are indexes in the boards
+const BINGO_NUMBERS: usize = i8::MAX as usize + 1;
+
+#[derive(Debug, Clone)]
 struct BingoBoard {
     data: [[i8; 5]; 5],
 
     lines: [i8; 5],
     columns: [i8; 5],
 
-    lines_index: [usize; i8::MAX as usize],
-    columns_index: [usize; i8::MAX as usize],
+    lines_index: [usize; BINGO_NUMBERS],
+    columns_index: [usize; BINGO_NUMBERS],
 
     draw_sum: i64,
 
@@ -23,8 +27,8 @@ impl Default for BingoBoard {
             lines: [0; 5],
             columns: [0; 5],
 
-            lines_index: [0xBADCAFE; i8::MAX as usize],
-            columns_index: [0xBADCAFE; i8::MAX as usize],
+            lines_index: [0xBADCAFE; BINGO_NUMBERS],
+            columns_index: [0xBADCAFE; BINGO_NUMBERS],
 
             draw_sum: 0,
 
@@ -33,40 +37,82 @@ impl Default for BingoBoard {
     }
 }
 
-fn day_4_core_algorithm(data: &str, part_2_mode: bool) -> i64 {
-    let mut lines = data.lines();
+#[derive(Debug)]
+pub struct Bingo {
+    pub draws: Vec<i8>,
+    boards: Vec<BingoBoard>,
+}
+
+// Bingo numbers are used as indexes, so they can't be negative
+fn parse_bingo_number(line_index: usize, line: &str, token: &str) -> Result<i8, ParseError> {
+    let number: i8 = parse_token(Day04::DAY, line_index, line, token, "bingo number")?;
+    if number < 0 {
+        return Err(ParseError::at_token(
+            Day04::DAY,
+            line_index,
+            line,
+            token,
+            "negative bingo number",
+        ));
+    }
+    return Ok(number);
+}
+
+pub fn parse_bingo(data: &str) -> Result<Bingo, ParseError> {
+    let mut lines = data.lines().enumerate();
 
-    let first_line = lines.next().expect("Could not read first line");
+    let (_, first_line) = lines
+        .next()
+        .ok_or_else(|| ParseError::new(Day04::DAY, 1, 1, "", "expected the drawn numbers"))?;
 
-    // Split the first line digits with the comma and parse them as i64
+    // Split the first line digits with the comma and parse them as i8
     let first_line_digits: Vec<i8> = first_line
-        .split(",")
-        .map(|x| x.parse::<i8>().expect("not a small number"))
-        .collect();
+        .split(',')
+        .map(|x| parse_bingo_number(0, first_line, x))
+        .collect::<Result<Vec<i8>, ParseError>>()?;
 
     let mut boards: Vec<BingoBoard> = vec![];
 
     loop {
         let blank_line = lines.next();
-        if blank_line.is_none() {
-            break;
+        match blank_line {
+            None => break,
+            Some((line_index, line)) if !line.trim().is_empty() => {
+                return Err(ParseError::at_token(
+                    Day04::DAY,
+                    line_index,
+                    line,
+                    line.trim(),
+                    "expected an empty line between the boards",
+                ));
+            }
+            _ => {}
         }
 
         let mut board = BingoBoard::default();
 
         // do 5 times
         for i in 0..5 {
-            let line = lines.next().expect("Could not read line");
+            let (line_index, line) = lines.next().ok_or_else(|| {
+                let line_count = data.lines().count();
+                ParseError::new(Day04::DAY, line_count + 1, 1, "", "expected a board line")
+            })?;
 
-            // Split the line digits with the comma and parse them as i64
+            // Split the line digits with the spaces and parse them as i8
             let line_digits: Vec<i8> = line
                 .split_whitespace()
-                .map(|x| x.parse::<i8>().expect("not a small number"))
-                .collect();
-
-            board.data[i] = line_digits
-                .try_into()
-                .expect("Line has wrong number of digits");
+                .map(|x| parse_bingo_number(line_index, line, x))
+                .collect::<Result<Vec<i8>, ParseError>>()?;
+
+            board.data[i] = line_digits.try_into().map_err(|_| {
+                ParseError::at_token(
+                    Day04::DAY,
+                    line_index,
+                    line,
+                    line.trim(),
+                    "expected 5 numbers",
+                )
+            })?;
         }
 
         // build board index
@@ -81,10 +127,20 @@ fn day_4_core_algorithm(data: &str, part_2_mode: bool) -> i64 {
         boards.push(board);
     }
 
+    return Ok(Bingo {
+        draws: first_line_digits,
+        boards,
+    });
+}
+
+fn day_4_core_algorithm(bingo: &Bingo, part_2_mode: bool) -> Result<i64, Error> {
+    // The boards are modified during the game
+    let mut boards = bingo.boards.clone();
+
     let number_of_boards = boards.len();
     let mut number_of_winning_boards = 0;
 
-    for draw_number in first_line_digits {
+    for &draw_number in &bingo.draws {
         for board in &mut boards {
             if part_2_mode && board.has_won {
                 continue;
@@ -104,33 +160,42 @@ fn day_4_core_algorithm(data: &str, part_2_mode: bool) -> i64 {
                 let total_sum = board
                     .data
                     .iter()
-                    .map(|x| {
-                        x.iter()
-                            .map(|n| i64::try_from(*n).expect("ah"))
-                            .sum::<i64>()
-                    })
+                    .map(|x| x.iter().map(|n| i64::from(*n)).sum::<i64>())
                     .sum::<i64>();
                 if part_2_mode {
                     board.has_won = true;
                     number_of_winning_boards += 1;
                     if number_of_winning_boards == number_of_boards {
-                        return (total_sum - board.draw_sum) * draw_number as i64;
+                        return Ok((total_sum - board.draw_sum) * draw_number as i64);
                     }
                 } else {
-                    return (total_sum - board.draw_sum) * draw_number as i64;
+                    return Ok((total_sum - board.draw_sum) * draw_number as i64);
                 }
             }
         }
     }
-    panic!("No solution found");
+    return Err(Error::no_solution(Day04::DAY, "No board won"));
 }
 
-pub fn day_4_part_1(data: &str) -> i64 {
-    return day_4_core_algorithm(data, false);
-}
+pub struct Day04;
 
-pub fn day_4_part_2(data: &str) -> i64 {
-    return day_4_core_algorithm(data, true);
+impl Solution for Day04 {
+    const DAY: u8 = 4;
+    const TITLE: &'static str = "Giant Squid";
+
+    type Input<'a> = Bingo;
+
+    fn parse(data: &str) -> Result<Bingo, ParseError> {
+        return parse_bingo(data);
+    }
+
+    fn part_1(bingo: &Bingo) -> Result<i64, Error> {
+        return day_4_core_algorithm(bingo, false);
+    }
+
+    fn part_2(bingo: &Bingo) -> Result<i64, Error> {
+        return day_4_core_algorithm(bingo, true);
+    }
 }
 
 #[cfg(test)]
@@ -159,11 +224,39 @@ mod tests {
 
     #[test]
     fn test_day_4_part_1() {
-        assert_eq!(day_4_part_1(EXAMPLE), 4512);
+        assert_eq!(Day04::solve_part_1(EXAMPLE), Ok(4512));
     }
 
     #[test]
     fn test_day_4_part_2() {
-        assert_eq!(day_4_part_2(EXAMPLE), 1924);
+        assert_eq!(Day04::solve_part_2(EXAMPLE), Ok(1924));
+    }
+
+    #[test]
+    fn test_largest_number() {
+        let data =
+            "127,1,2,3,4\n\n127 1 2 3 4\n5 6 7 8 9\n10 11 12 13 14\n15 16 17 18 19\n20 21 22 23 24";
+        let sum = (5..=24).sum::<i64>();
+        assert_eq!(Day04::solve_part_1(data), Ok(sum * 4));
+        assert_eq!(
+            parse_bingo("7,128").unwrap_err(),
+            ParseError::new(4, 1, 3, "128", "invalid bingo number")
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_bingo("7,4,x9").unwrap_err(),
+            ParseError::new(4, 1, 5, "x9", "invalid bingo number")
+        );
+        assert_eq!(
+            parse_bingo("7,4\n\n1 2 3 4 5\n1 2 3 4").unwrap_err(),
+            ParseError::new(4, 4, 1, "1 2 3 4", "expected 5 numbers")
+        );
+        assert_eq!(
+            parse_bingo("7,4\n\n1 2 3 4 5").unwrap_err(),
+            ParseError::new(4, 4, 1, "", "expected a board line")
+        );
     }
 }
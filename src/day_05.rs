@@ -1,110 +1,404 @@
-#[derive(Debug)]
-pub struct Map {
-    // data is an array of arrays of i64s
-    // data[x][y] is the value at the x,y coordinate
-    data: Vec<Vec<i64>>,
-}
-
-impl Default for Map {
-    fn default() -> Self {
-        Map {
-            // Create a vector of 1000 vectors of 1000 i64s 0
-            data: vec![vec![0; 1000]; 1000],
-        }
+use std::collections::{BTreeSet, HashMap};
+
+use crate::error::{parse_token, Error, ParseError};
+use crate::grid::Grid;
+use crate::solution::Solution;
+
+// map[(x, y)] is the number of lines on the x,y coordinate
+pub type Map = Grid<i64>;
+
+pub const MAP_SIZE: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment {
+    pub x1: i64,
+    pub y1: i64,
+    pub x2: i64,
+    pub y2: i64,
+}
+
+impl Segment {
+    // Neither horizontal nor vertical, at any angle
+    pub fn is_diagonal(&self) -> bool {
+        return self.x1 != self.x2 && self.y1 != self.y2;
+    }
+
+    /**
+     * The points with integer coordinates from (x1, y1) to (x2, y2), for any
+     * slope. Dividing the move by the gcd of dx and dy gives the smallest
+     * step between two of them, so 0,0 -> 2,6 is 0,0 then 1,3 then 2,6.
+     */
+    pub fn points(&self) -> impl Iterator<Item = (i64, i64)> {
+        // In i128, the differences could overflow between i64::MIN and i64::MAX
+        let (x1, y1) = (i128::from(self.x1), i128::from(self.y1));
+        let dx = i128::from(self.x2) - x1;
+        let dy = i128::from(self.y2) - y1;
+        let steps = gcd(dx.unsigned_abs(), dy.unsigned_abs()) as i128;
+        let (x_unit, y_unit) = if steps == 0 {
+            (0, 0)
+        } else {
+            (dx / steps, dy / steps)
+        };
+        // Every point is between the two ends, so it fits in an i64
+        return (0..=steps).map(move |i| ((x1 + x_unit * i) as i64, (y1 + y_unit * i) as i64));
     }
 }
 
-pub fn parse_map(data: &str, diagonal: bool) -> Map {
-    let mut lines = data.lines();
-
-    // Generated by Github Copilot
-
-    let mut map = Map::default();
-
-    while let Some(line) = lines.next() {
-        let mut from_to_split = line.split(" -> ");
-        let from = from_to_split.next().expect("No line segment");
-        let mut from_split = from.split(",");
-        let mut x1 = from_split
-            .next()
-            .expect("No x1")
-            .parse::<i64>()
-            .expect("Could not parse x1");
-        let mut y1 = from_split
-            .next()
-            .expect("No y1")
-            .parse::<i64>()
-            .expect("Could not parse y1");
-        let to = from_to_split.next().expect("No line segment");
-        let mut to_split = to.split(",");
-        let mut x2 = to_split
-            .next()
-            .expect("No x2")
-            .parse::<i64>()
-            .expect("Could not parse x2");
-        let mut y2 = to_split
-            .next()
-            .expect("no y2")
-            .parse::<i64>()
-            .expect("Could not parse y2");
-
-        if x1 == x2 {
-            if y1 > y2 {
-                std::mem::swap(&mut y1, &mut y2);
-            }
-            for y in y1..=y2 {
-                map.data[x1 as usize][y as usize] += 1;
-            }
-        } else if y1 == y2 {
-            if x1 > x2 {
-                std::mem::swap(&mut x1, &mut x2);
-            }
-            for x in x1..=x2 {
-                map.data[x as usize][y1 as usize] += 1;
-            }
-        } else if diagonal {
-            let x_unit = if (x2 - x1) > 0 { 1 } else { -1 };
-            let y_unit = if (y2 - y1) > 0 { 1 } else { -1 };
-
-            while x1 != x2 || y1 != y2 {
-                map.data[x1 as usize][y1 as usize] += 1;
-                x1 += x_unit;
-                y1 += y_unit;
-            }
-            map.data[x1 as usize][y1 as usize] += 1;
+// Parse "x,y" coordinates
+fn parse_point(line_index: usize, line: &str, point: &str) -> Result<(i64, i64), ParseError> {
+    let (x, y) = point.split_once(',').ok_or_else(|| {
+        ParseError::at_token(
+            Day05::DAY,
+            line_index,
+            line,
+            point,
+            "expected x,y coordinates",
+        )
+    })?;
+    let x = parse_token(Day05::DAY, line_index, line, x, "x coordinate")?;
+    let y = parse_token(Day05::DAY, line_index, line, y, "y coordinate")?;
+    return Ok((x, y));
+}
+
+pub fn parse_segments(data: &str) -> Result<Vec<Segment>, ParseError> {
+    return data
+        .lines()
+        .enumerate()
+        .map(|(line_index, line)| {
+            let (from, to) = line.split_once(" -> ").ok_or_else(|| {
+                ParseError::at_token(
+                    Day05::DAY,
+                    line_index,
+                    line,
+                    line,
+                    "expected a line segment like x1,y1 -> x2,y2",
+                )
+            })?;
+            let (x1, y1) = parse_point(line_index, line, from)?;
+            let (x2, y2) = parse_point(line_index, line, to)?;
+            return Ok(Segment { x1, y1, x2, y2 });
+        })
+        .collect();
+}
+
+fn gcd(a: u128, b: u128) -> u128 {
+    if b == 0 {
+        return a;
+    }
+    return gcd(b, a % b);
+}
+
+// The dense map only has room for coordinates between 0 and 999
+pub fn fits_in_map(segments: &[Segment]) -> bool {
+    let in_map = |coordinate: i64| (0..MAP_SIZE as i64).contains(&coordinate);
+    return segments
+        .iter()
+        .all(|s| in_map(s.x1) && in_map(s.y1) && in_map(s.x2) && in_map(s.y2));
+}
+
+// Panics if a segment doesn't fit in the map
+pub fn build_map(segments: &[Segment], diagonal: bool) -> Map {
+    let mut map = Map::new(MAP_SIZE, MAP_SIZE, 0);
+
+    for segment in segments {
+        if !diagonal && segment.is_diagonal() {
+            continue;
+        }
+        for (x, y) in segment.points() {
+            map[(x as usize, y as usize)] += 1;
         }
     }
 
     return map;
 }
 
+pub fn parse_map(data: &str, diagonal: bool) -> Result<Map, ParseError> {
+    let segments = parse_segments(data)?;
+    // One segment per line
+    for ((line_index, line), segment) in data.lines().enumerate().zip(&segments) {
+        if !fits_in_map(std::slice::from_ref(segment)) {
+            return Err(ParseError::at_token(
+                Day05::DAY,
+                line_index,
+                line,
+                line,
+                format!(
+                    "the segment doesn't fit in the {}x{} map",
+                    MAP_SIZE, MAP_SIZE
+                ),
+            ));
+        }
+    }
+    return Ok(build_map(&segments, diagonal));
+}
+
 fn sum_of_above_1(map: &Map) -> i64 {
-    let mut sum = 0;
+    return map.iter().filter(|&&lines| lines > 1).count() as i64;
+}
+
+// Only the points with a line are stored, but every one of them is, so the
+// segments must be short
+pub fn build_sparse_map(segments: &[Segment], diagonal: bool) -> HashMap<(i64, i64), u32> {
+    let layer = if diagonal {
+        Layer::All
+    } else {
+        Layer::Straight
+    };
+    return build_layer_map(segments, layer);
+}
+
+// Some of the segments, to look at them separately
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    All,
+    // Horizontal and vertical
+    Straight,
+    Diagonal,
+}
+
+impl Layer {
+    pub fn includes(self, segment: &Segment) -> bool {
+        return match self {
+            Layer::All => true,
+            Layer::Straight => !segment.is_diagonal(),
+            Layer::Diagonal => segment.is_diagonal(),
+        };
+    }
+}
+
+pub fn build_layer_map(segments: &[Segment], layer: Layer) -> HashMap<(i64, i64), u32> {
+    let mut map: HashMap<(i64, i64), u32> = HashMap::new();
+    for segment in segments.iter().filter(|segment| layer.includes(segment)) {
+        for point in segment.points() {
+            *map.entry(point).or_insert(0) += 1;
+        }
+    }
+    return map;
+}
+
+pub fn count_overlaps_dense(segments: &[Segment], diagonal: bool) -> i64 {
+    return sum_of_above_1(&build_map(segments, diagonal));
+}
+
+fn too_large() -> Error {
+    return Error::no_solution(
+        Day05::DAY,
+        "The coordinates are too large to cross the segments",
+    );
+}
+
+// a.0 * b.1 - a.1 * b.0, None when it doesn't fit in an i128
+fn cross(a: (i128, i128), b: (i128, i128)) -> Option<i128> {
+    return a.0.checked_mul(b.1)?.checked_sub(a.1.checked_mul(b.0)?);
+}
+
+/**
+ * The line of a segment, ux * y - uy * x = c where (ux, uy) is the smallest
+ * step between two of its points. The points of the line are numbered by
+ * x / ux, or y / uy when it's vertical, so a segment is a range of numbers.
+ * A single point is on the horizontal line.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Line {
+    ux: i128,
+    uy: i128,
+    c: i128,
+}
+
+impl Line {
+    fn of(segment: &Segment) -> Result<Line, Error> {
+        let dx = i128::from(segment.x2) - i128::from(segment.x1);
+        let dy = i128::from(segment.y2) - i128::from(segment.y1);
+        let steps = gcd(dx.unsigned_abs(), dy.unsigned_abs()) as i128;
+        let (ux, uy) = match steps {
+            0 => (1, 0),
+            _ if dx < 0 || (dx == 0 && dy < 0) => (-dx / steps, -dy / steps),
+            _ => (dx / steps, dy / steps),
+        };
+        let c = cross((ux, uy), (i128::from(segment.x1), i128::from(segment.y1)))
+            .ok_or_else(too_large)?;
+        return Ok(Line { ux, uy, c });
+    }
+
+    fn number(&self, (x, y): (i128, i128)) -> i128 {
+        if self.ux != 0 {
+            return x.div_euclid(self.ux);
+        }
+        return y.div_euclid(self.uy);
+    }
 
-    for x in 0..1000 {
-        for y in 0..1000 {
-            if map.data[x][y] > 1 {
-                sum += 1;
+    fn range(&self, segment: &Segment) -> (i128, i128) {
+        let start = self.number((i128::from(segment.x1), i128::from(segment.y1)));
+        let end = self.number((i128::from(segment.x2), i128::from(segment.y2)));
+        return (start.min(end), start.max(end));
+    }
+}
+
+// The parts covered by at least two of the ranges, sorted
+fn overlapping_ranges(ranges: &[(i128, i128)]) -> Vec<(i128, i128)> {
+    let mut events: Vec<(i128, i32)> = ranges
+        .iter()
+        .flat_map(|&(start, end)| [(start, 1), (end + 1, -1)])
+        .collect();
+    events.sort_unstable();
+
+    let mut overlaps = vec![];
+    let mut covered = 0;
+    let mut overlap_start = 0;
+    for (position, change) in events {
+        let was_overlapping = covered > 1;
+        covered += change;
+        if !was_overlapping && covered > 1 {
+            overlap_start = position;
+        } else if was_overlapping && covered <= 1 && position > overlap_start {
+            overlaps.push((overlap_start, position - 1));
+        }
+    }
+    return overlaps;
+}
+
+fn in_ranges(ranges: &[(i128, i128)], number: i128) -> bool {
+    let index = ranges.partition_point(|&(_, end)| end < number);
+    return index < ranges.len() && ranges[index].0 <= number;
+}
+
+fn in_bounds(segment: &Segment, (x, y): (i128, i128)) -> bool {
+    let (x1, x2) = (i128::from(segment.x1), i128::from(segment.x2));
+    let (y1, y2) = (i128::from(segment.y1), i128::from(segment.y2));
+    return (x1.min(x2)..=x1.max(x2)).contains(&x) && (y1.min(y2)..=y1.max(y2)).contains(&y);
+}
+
+// The point where the segments of two different lines cross, if it has integer coordinates
+fn crossing(a: (&Segment, &Line), b: (&Segment, &Line)) -> Result<Option<(i128, i128)>, Error> {
+    let ((segment_a, line_a), (segment_b, line_b)) = (a, b);
+    let determinant =
+        cross((line_a.ux, line_a.uy), (line_b.ux, line_b.uy)).ok_or_else(too_large)?;
+    if determinant == 0 {
+        // Parallel
+        return Ok(None);
+    }
+    let x = cross((line_a.c, line_b.c), (line_a.ux, line_b.ux)).ok_or_else(too_large)?;
+    let y = cross((line_a.c, line_b.c), (line_a.uy, line_b.uy)).ok_or_else(too_large)?;
+    if x.checked_rem(determinant).ok_or_else(too_large)? != 0
+        || y.checked_rem(determinant).ok_or_else(too_large)? != 0
+    {
+        return Ok(None);
+    }
+    let point = (x / determinant, y / determinant);
+    if in_bounds(segment_a, point) && in_bounds(segment_b, point) {
+        return Ok(Some(point));
+    }
+    return Ok(None);
+}
+
+/**
+ * Without listing the points, so the segments can be as long as an i64 allows.
+ *
+ * The segments on the same line overlap on ranges of points. The segments of
+ * different lines cross on at most one point, which may also be in one of
+ * these ranges.
+ */
+pub fn count_overlaps_sparse(segments: &[Segment], diagonal: bool) -> Result<i64, Error> {
+    let mut segments: Vec<&Segment> = segments
+        .iter()
+        .filter(|segment| diagonal || !segment.is_diagonal())
+        .collect();
+    // So the segments that can cross are next to each other
+    segments.sort_unstable_by_key(|segment| segment.x1.min(segment.x2));
+    let lines = segments
+        .iter()
+        .map(|segment| Line::of(segment))
+        .collect::<Result<Vec<Line>, Error>>()?;
+
+    let mut ranges: HashMap<Line, Vec<(i128, i128)>> = HashMap::new();
+    for (segment, line) in segments.iter().zip(&lines) {
+        ranges.entry(*line).or_default().push(line.range(segment));
+    }
+    let overlaps: HashMap<Line, Vec<(i128, i128)>> = ranges
+        .iter()
+        .map(|(line, ranges)| (*line, overlapping_ranges(ranges)))
+        .collect();
+    let mut count: i128 = overlaps
+        .values()
+        .flatten()
+        .map(|&(start, end)| end - start + 1)
+        .sum();
+
+    // Every line through a crossing crosses the others there, so a crossing
+    // is counted once, and only when none of its lines has it in an overlap
+    let mut crossings: HashMap<(i128, i128), bool> = HashMap::new();
+    for (i, (segment_a, line_a)) in segments.iter().zip(&lines).enumerate() {
+        let max_x = segment_a.x1.max(segment_a.x2);
+        for (segment_b, line_b) in segments[i + 1..].iter().zip(&lines[i + 1..]) {
+            if segment_b.x1.min(segment_b.x2) > max_x {
+                break;
+            }
+            if line_a == line_b {
+                continue;
+            }
+            if let Some(point) = crossing((segment_a, line_a), (segment_b, line_b))? {
+                let in_overlap = [line_a, line_b]
+                    .iter()
+                    .any(|line| in_ranges(&overlaps[line], line.number(point)));
+                *crossings.entry(point).or_insert(false) |= in_overlap;
             }
         }
     }
+    count += crossings
+        .values()
+        .filter(|&&in_overlap| !in_overlap)
+        .count() as i128;
+
+    return i64::try_from(count)
+        .map_err(|_| Error::no_solution(Day05::DAY, "More overlaps than an i64 can count"));
+}
 
-    return sum;
+// The points covered by at least min_overlaps segments, sorted
+pub fn overlapping_points(
+    segments: &[Segment],
+    diagonal: bool,
+    min_overlaps: u32,
+) -> BTreeSet<(i64, i64)> {
+    return build_sparse_map(segments, diagonal)
+        .into_iter()
+        .filter(|&(_, lines)| lines >= min_overlaps)
+        .map(|(point, _)| point)
+        .collect();
 }
 
-pub fn day_5_part_1(data: &str) -> i64 {
-    let map = parse_map(data, false);
-    return sum_of_above_1(&map);
+// The dense map is faster for the puzzle inputs, the sparse count works with any input
+pub fn count_overlaps(segments: &[Segment], diagonal: bool) -> Result<i64, Error> {
+    if fits_in_map(segments) {
+        return Ok(count_overlaps_dense(segments, diagonal));
+    }
+    return count_overlaps_sparse(segments, diagonal);
 }
 
-pub fn day_5_part_2(data: &str) -> i64 {
-    let map = parse_map(data, true);
-    return sum_of_above_1(&map);
+pub struct Day05;
+
+impl Solution for Day05 {
+    const DAY: u8 = 5;
+    const TITLE: &'static str = "Hydrothermal Venture";
+
+    type Input<'a> = Vec<Segment>;
+
+    fn parse(data: &str) -> Result<Vec<Segment>, ParseError> {
+        return parse_segments(data);
+    }
+
+    fn part_1(segments: &Vec<Segment>) -> Result<i64, Error> {
+        return count_overlaps(segments, false);
+    }
+
+    fn part_2(segments: &Vec<Segment>) -> Result<i64, Error> {
+        return count_overlaps(segments, true);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     const EXAMPLE: &str = "0,9 -> 5,9
 8,0 -> 0,8
@@ -119,11 +413,158 @@ mod tests {
 
     #[test]
     fn test_day_5_part_1() {
-        assert_eq!(day_5_part_1(EXAMPLE), 5);
+        assert_eq!(Day05::solve_part_1(EXAMPLE), Ok(5));
     }
 
     #[test]
     fn test_day_5_part_2() {
-        assert_eq!(day_5_part_2(EXAMPLE), 12);
+        assert_eq!(Day05::solve_part_2(EXAMPLE), Ok(12));
+    }
+
+    #[test]
+    fn test_sparse_map() {
+        let segments = parse_segments(EXAMPLE).unwrap();
+        for diagonal in [false, true] {
+            assert_eq!(
+                count_overlaps_sparse(&segments, diagonal),
+                Ok(count_overlaps_dense(&segments, diagonal))
+            );
+        }
+
+        // Outside of the dense map
+        let segments = parse_segments(
+            "-5,-5 -> 5,5\n-5,5 -> 5,-5\n0,-3 -> 0,3\n4000000000000,7 -> 4000000000000,-7\n3999999999999,0 -> 4000000000001,0",
+        )
+        .unwrap();
+        assert!(!fits_in_map(&segments));
+        assert_eq!(count_overlaps(&segments, false), Ok(1));
+        assert_eq!(count_overlaps(&segments, true), Ok(2));
+        assert_eq!(build_sparse_map(&segments, true)[&(0, 0)], 3);
+    }
+
+    #[test]
+    fn test_huge_segments() {
+        let count = |data: &str, diagonal: bool| {
+            return count_overlaps(&parse_segments(data).unwrap(), diagonal);
+        };
+        assert_eq!(count("0,0 -> 4000000000000,0\n5,-3 -> 5,3", true), Ok(1));
+        assert_eq!(
+            count("0,0 -> 4000000000000,0\n4000000000000,0 -> 5,0", false),
+            Ok(3999999999996)
+        );
+        // The crossing at 6,6 is also on the overlap of the two diagonals
+        assert_eq!(
+            count(
+                "-9223372036854775808,-9223372036854775808 -> 9223372036854775807,9223372036854775807\n\
+                 9,9 -> 0,0\n0,12 -> 12,0\n6,6 -> 6,6",
+                true
+            ),
+            Ok(10)
+        );
+        assert_eq!(
+            count("0,0 -> 2,6\n0,6 -> 2,0\n0,3 -> 4000000000000,3", true),
+            Ok(1)
+        );
+        assert_eq!(
+            count(
+                "-9223372036854775808,0 -> 9223372036854775807,0\n\
+                 9223372036854775807,0 -> -9223372036854775808,0",
+                false
+            ),
+            Err(Error::no_solution(5, "More overlaps than an i64 can count"))
+        );
+    }
+
+    #[test]
+    fn test_any_slope() {
+        let points = |segment: &str| {
+            let segments = parse_segments(segment).unwrap();
+            return segments[0].points().collect::<Vec<(i64, i64)>>();
+        };
+        assert_eq!(points("0,0 -> 2,6"), [(0, 0), (1, 3), (2, 6)]);
+        assert_eq!(points("6,3 -> 0,-1"), [(6, 3), (3, 1), (0, -1)]);
+        assert_eq!(points("0,0 -> 3,2"), [(0, 0), (3, 2)]);
+        assert_eq!(points("4,4 -> 4,4"), [(4, 4)]);
+        assert_eq!(points("1,3 -> 1,1"), [(1, 3), (1, 2), (1, 1)]);
+        // Without overflows, gcd(2^64 - 1, 2^64 - 2) is 1
+        assert_eq!(
+            points("-9223372036854775808,-9223372036854775808 -> 9223372036854775807,9223372036854775806"),
+            [(i64::MIN, i64::MIN), (i64::MAX, i64::MAX - 1)]
+        );
+
+        // Steep segments stay in the map
+        let segments = parse_segments("0,0 -> 2,6\n0,6 -> 2,0\n0,3 -> 4,3\n1,0 -> 1,1").unwrap();
+        assert_eq!(count_overlaps_dense(&segments, true), 1);
+        assert_eq!(
+            overlapping_points(&segments, true, 2),
+            BTreeSet::from([(1, 3)])
+        );
+        assert_eq!(overlapping_points(&segments, true, 3).len(), 1);
+        assert_eq!(overlapping_points(&segments, false, 2).len(), 0);
+
+        let segments = parse_segments(EXAMPLE).unwrap();
+        assert_eq!(overlapping_points(&segments, true, 2).len(), 12);
+        assert_eq!(
+            overlapping_points(&segments, true, 3),
+            BTreeSet::from([(4, 4), (6, 4)])
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_segments("0,9 -> 5,9\n9,4 -> x,4").unwrap_err(),
+            ParseError::new(5, 2, 8, "x", "invalid x coordinate")
+        );
+        assert_eq!(
+            parse_segments("0,9 -> 5").unwrap_err(),
+            ParseError::new(5, 1, 8, "5", "expected x,y coordinates")
+        );
+        assert_eq!(
+            parse_segments("0,9 5,9").unwrap_err(),
+            ParseError::new(
+                5,
+                1,
+                1,
+                "0,9 5,9",
+                "expected a line segment like x1,y1 -> x2,y2"
+            )
+        );
+        assert_eq!(
+            parse_map("0,9 -> 5,9\n-1,0 -> 3,4", true).unwrap_err(),
+            ParseError::new(
+                5,
+                2,
+                1,
+                "-1,0 -> 3,4",
+                "the segment doesn't fit in the 1000x1000 map"
+            )
+        );
+        assert!(parse_map("0,0 -> 1000,0", false).is_err());
+        assert_eq!(parse_map("0,0 -> 999,999", true).unwrap()[(999, 999)], 1);
+    }
+
+    fn segment() -> impl Strategy<Value = Segment> {
+        // Small, so they overlap a lot, and with some single points
+        return (-8..8i64, -8..8i64, -8..8i64, -8..8i64).prop_map(|(x1, y1, x2, y2)| Segment {
+            x1,
+            y1,
+            x2,
+            y2,
+        });
+    }
+
+    proptest! {
+        #[test]
+        fn test_sparse_count_is_the_points_count(
+            segments in prop::collection::vec(segment(), 0..30),
+            diagonal in any::<bool>()
+        ) {
+            let points = build_sparse_map(&segments, diagonal)
+                .values()
+                .filter(|&&lines| lines > 1)
+                .count() as i64;
+            prop_assert_eq!(count_overlaps_sparse(&segments, diagonal), Ok(points));
+        }
     }
 }
@@ -1,22 +1,40 @@
-fn parse_input_numbers(input: &str) -> Vec<i8> {
-    input
-        .split(",")
-        .map(|s| s.parse::<i8>().expect("Could not parse number"))
-        .collect()
+use crate::error::{parse_token, Error, ParseError};
+use crate::solution::Solution;
+
+pub fn parse_input_numbers(input: &str) -> Result<Vec<i8>, ParseError> {
+    let mut fishes = vec![];
+    for (line_index, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        for token in line.split(',') {
+            let fish: i8 = parse_token(Day06::DAY, line_index, line, token, "fish timer")?;
+            if !(0..=8).contains(&fish) {
+                return Err(ParseError::at_token(
+                    Day06::DAY,
+                    line_index,
+                    line,
+                    token,
+                    "fish timers are between 0 and 8",
+                ));
+            }
+            fishes.push(fish);
+        }
+    }
+    return Ok(fishes);
 }
 
-fn _slow_fishes_simulation(fishes: Vec<i8>, generation: u64) -> i64 {
-    let mut fishes = fishes;
+// Kept to compare with the faster one in the benches, don't go much past 100 generations
+pub fn slow_fishes_simulation(fishes: &[i8], generation: u64) -> i64 {
+    let mut fishes = fishes.to_vec();
     for _i in 0..generation {
-        println!("Computing generation {}", _i + 1);
         let mut new_fishes: Vec<i8> = vec![];
-        for i in 0..fishes.len() {
-            let fish = fishes[i];
-            if fish == 0 {
-                fishes[i] = 6;
+        for fish in fishes.iter_mut() {
+            if *fish == 0 {
+                *fish = 6;
                 new_fishes.push(8);
             } else {
-                fishes[i] -= 1;
+                *fish -= 1;
             }
         }
         fishes.append(&mut new_fishes);
@@ -24,7 +42,7 @@ fn _slow_fishes_simulation(fishes: Vec<i8>, generation: u64) -> i64 {
     return fishes.len() as i64;
 }
 
-fn faster_fishes_simulation(fishes: Vec<i8>, generation: u64) -> i64 {
+pub fn faster_fishes_simulation(fishes: &[i8], generation: u64) -> i64 {
     let mut generation_0: u64 = 0;
     let mut generation_1: u64 = 0;
     let mut generation_2: u64 = 0;
@@ -44,6 +62,8 @@ fn faster_fishes_simulation(fishes: Vec<i8>, generation: u64) -> i64 {
             4 => generation_4 += 1,
             5 => generation_5 += 1,
             6 => generation_6 += 1,
+            7 => generation_7 += 1,
+            8 => generation_8 += 1,
             _ => panic!("Invalid fish {}", fish),
         }
     }
@@ -75,15 +95,26 @@ fn faster_fishes_simulation(fishes: Vec<i8>, generation: u64) -> i64 {
     .expect("Could not convert to i64");
 }
 
-pub fn day_6_part_1(data: &str) -> i64 {
-    let fishes = parse_input_numbers(data);
-    //return slow_fishes_simulation(fishes, 80);
-    return faster_fishes_simulation(fishes, 80);
-}
+pub struct Day06;
+
+impl Solution for Day06 {
+    const DAY: u8 = 6;
+    const TITLE: &'static str = "Lanternfish";
+
+    type Input<'a> = Vec<i8>;
+
+    fn parse(data: &str) -> Result<Vec<i8>, ParseError> {
+        return parse_input_numbers(data);
+    }
 
-pub fn day_6_part_2(data: &str) -> i64 {
-    let fishes = parse_input_numbers(data);
-    return faster_fishes_simulation(fishes, 256);
+    fn part_1(fishes: &Vec<i8>) -> Result<i64, Error> {
+        //return slow_fishes_simulation(fishes, 80);
+        return Ok(faster_fishes_simulation(fishes, 80));
+    }
+
+    fn part_2(fishes: &Vec<i8>) -> Result<i64, Error> {
+        return Ok(faster_fishes_simulation(fishes, 256));
+    }
 }
 
 #[cfg(test)]
@@ -94,11 +125,27 @@ mod tests {
 
     #[test]
     fn test_day_6_part_1() {
-        assert_eq!(day_6_part_1(EXAMPLE), 5934);
+        assert_eq!(Day06::solve_part_1(EXAMPLE), Ok(5934));
     }
 
     #[test]
     fn test_day_6_part_2() {
-        assert_eq!(day_6_part_2(EXAMPLE), 26984457539);
+        assert_eq!(Day06::solve_part_2(EXAMPLE), Ok(26984457539));
+    }
+
+    #[test]
+    fn test_slow_fishes_simulation() {
+        let fishes = parse_input_numbers(EXAMPLE).unwrap();
+        assert_eq!(slow_fishes_simulation(&fishes, 18), 26);
+        assert_eq!(faster_fishes_simulation(&fishes, 18), 26);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse_input_numbers("3,4\n"), Ok(vec![3, 4]));
+        assert_eq!(
+            parse_input_numbers("3,4,9").unwrap_err(),
+            ParseError::new(6, 1, 5, "9", "fish timers are between 0 and 8")
+        );
     }
 }
@@ -1,15 +1,32 @@
 use ndarray_stats::Quantile1dExt;
 
-fn parse_input_numbers(input: &str) -> Vec<i64> {
-    input
-        .split(",")
-        .map(|s| s.parse::<i64>().expect("Could not parse number"))
-        .collect()
+use crate::error::{parse_token, Error, ParseError};
+use crate::solution::Solution;
+
+pub fn parse_input_numbers(input: &str) -> Result<Vec<i64>, ParseError> {
+    let mut crabs = vec![];
+    for (line_index, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        for token in line.split(',') {
+            crabs.push(parse_token(
+                Day07::DAY,
+                line_index,
+                line,
+                token,
+                "crab position",
+            )?);
+        }
+    }
+    if crabs.is_empty() {
+        return Err(ParseError::new(Day07::DAY, 1, 1, "", "no crabs"));
+    }
+    return Ok(crabs);
 }
 
-pub fn day_7_part_1(data: &str) -> i64 {
-    let crabs = parse_input_numbers(data);
-    let mut nd_crabs = ndarray::Array::from_vec(crabs.clone());
+fn minimal_fuel(crabs: &[i64]) -> i64 {
+    let mut nd_crabs = ndarray::Array::from_vec(crabs.to_vec());
     let median = nd_crabs
         .quantile_mut(
             noisy_float::types::n64(0.5),
@@ -25,9 +42,8 @@ fn fuel(d: i64) -> i64 {
     return d * (d + 1) / 2;
 }
 
-pub fn day_7_part_2(data: &str) -> i64 {
-    let crabs = parse_input_numbers(data);
-    let nd_crabs = ndarray::Array::from_vec(crabs.clone());
+fn minimal_crab_fuel(crabs: &[i64]) -> i64 {
+    let nd_crabs = ndarray::Array::from_vec(crabs.to_vec());
     let mean = nd_crabs.mean().expect("Could not calculate mean");
     let a = crabs
         .iter()
@@ -42,6 +58,27 @@ pub fn day_7_part_2(data: &str) -> i64 {
     return a.min(b);
 }
 
+pub struct Day07;
+
+impl Solution for Day07 {
+    const DAY: u8 = 7;
+    const TITLE: &'static str = "The Treachery of Whales";
+
+    type Input<'a> = Vec<i64>;
+
+    fn parse(data: &str) -> Result<Vec<i64>, ParseError> {
+        return parse_input_numbers(data);
+    }
+
+    fn part_1(crabs: &Vec<i64>) -> Result<i64, Error> {
+        return Ok(minimal_fuel(crabs));
+    }
+
+    fn part_2(crabs: &Vec<i64>) -> Result<i64, Error> {
+        return Ok(minimal_crab_fuel(crabs));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -50,11 +87,11 @@ mod tests {
 
     #[test]
     fn test_day_7_part_1() {
-        assert_eq!(day_7_part_1(EXAMPLE), 37);
+        assert_eq!(Day07::solve_part_1(EXAMPLE), Ok(37));
     }
 
     #[test]
     fn test_day_7_part_2() {
-        assert_eq!(day_7_part_2(EXAMPLE), 168);
+        assert_eq!(Day07::solve_part_2(EXAMPLE), Ok(168));
     }
 }
@@ -1,7 +1,10 @@
+use crate::error::{Error, ParseError};
+use crate::solution::Solution;
+
 #[derive(Debug)]
-struct DisplayObservations {
-    patterns: Vec<String>,
-    outputs: Vec<String>,
+pub struct DisplayObservations {
+    pub patterns: Vec<String>,
+    pub outputs: Vec<String>,
 }
 
 #[derive(Debug)]
@@ -9,80 +12,75 @@ struct Display {
     digits: [String; 10],
 }
 
-fn parse_data(data: &str) -> Vec<DisplayObservations> {
+// Segment letters are a to g, and a digit lights between 2 and 7 segments
+fn parse_patterns(
+    line_index: usize,
+    line: &str,
+    patterns: &str,
+    expected: usize,
+    what: &str,
+) -> Result<Vec<String>, ParseError> {
+    let patterns: Vec<&str> = patterns.split_whitespace().collect();
+    for pattern in &patterns {
+        for (index, c) in pattern.char_indices() {
+            if !('a'..='g').contains(&c) {
+                return Err(ParseError::at_token(
+                    Day08::DAY,
+                    line_index,
+                    line,
+                    &pattern[index..index + c.len_utf8()],
+                    "segments are letters from a to g",
+                ));
+            }
+        }
+        if !(2..=7).contains(&pattern.len()) {
+            return Err(ParseError::at_token(
+                Day08::DAY,
+                line_index,
+                line,
+                pattern,
+                "a digit has between 2 and 7 segments",
+            ));
+        }
+    }
+    if patterns.len() != expected {
+        return Err(ParseError::at_token(
+            Day08::DAY,
+            line_index,
+            line,
+            line,
+            format!("expected {} {}", expected, what),
+        ));
+    }
+    return Ok(patterns.iter().map(|s| s.to_string()).collect());
+}
+
+pub fn parse_data(data: &str) -> Result<Vec<DisplayObservations>, ParseError> {
     // read data line by line
     // 10 patterns strings, a |, and 4 output strings
     return data
         .lines()
-        .map(|line| {
-            let mut parts = line.split(" | ");
-            let patterns: Vec<String> = parts
-                .next()
-                .expect("Could not get patterns")
-                .split(" ")
-                .map(|s| s.trim().to_string())
-                .collect();
-            let outputs: Vec<String> = parts
-                .next()
-                .expect("Could not get outputs")
-                .split(" ")
-                .map(|s| s.trim().to_string())
-                .collect();
-            if (patterns.len() != 10) || (outputs.len() != 4) {
-                panic!("Invalid data line: {}", line);
-            }
-            // check if patterns contains only letters from a to g
-            for pattern in &patterns {
-                for c in pattern.chars() {
-                    if !(c == 'a'
-                        || c == 'b'
-                        || c == 'c'
-                        || c == 'd'
-                        || c == 'e'
-                        || c == 'f'
-                        || c == 'g')
-                    {
-                        panic!("Invalid pattern: {}", pattern);
-                    }
-                }
-            }
-            // same for outputs
-            for output in outputs.iter() {
-                for c in output.chars() {
-                    if !(c == 'a'
-                        || c == 'b'
-                        || c == 'c'
-                        || c == 'd'
-                        || c == 'e'
-                        || c == 'f'
-                        || c == 'g')
-                    {
-                        panic!("Invalid output: {}", c);
-                    }
-                }
-            }
-            return DisplayObservations { patterns, outputs };
+        .enumerate()
+        .map(|(line_index, line)| {
+            let (patterns, outputs) = line.split_once('|').ok_or_else(|| {
+                ParseError::at_end_of_line(Day08::DAY, line_index, line, "expected a |")
+            })?;
+            let patterns = parse_patterns(line_index, line, patterns, 10, "patterns")?;
+            let outputs = parse_patterns(line_index, line, outputs, 4, "outputs")?;
+            return Ok(DisplayObservations { patterns, outputs });
         })
         .collect();
 }
 
-pub fn day_8_part_1(data: &str) -> i64 {
-    let observations = parse_data(data);
+fn count_easy_digits(observations: &[DisplayObservations]) -> i64 {
     return observations
         .iter()
         .map(|observation| {
             let sum: i64 = observation
                 .outputs
                 .iter()
-                .map(|output| match output.len() {
-                    2 => 1, // 1
-                    3 => 1, // 7,
-                    4 => 1, // 4,
-                    5 => 0,
-                    6 => 0,
-                    7 => 1, // 8,
-                    _ => panic!("Invalid output length: {}", output.len()),
-                })
+                // 1, 7, 4 and 8
+                .map(|output| i64::from(matches!(output.len(), 2 | 3 | 4 | 7)))
                 .sum();
             return sum;
         })
@@ -90,8 +88,16 @@ pub fn day_8_part_1(data: &str) -> i64 {
 }
 
 // Takes a list of 10 observations for each digit in an unknown random order
-// and returns the corresponding display definition.
-fn solve_display_problem(observation: &DisplayObservations) -> Display {
+// and returns the corresponding display definition, or an error when they are
+// not the 10 digits. line_index is only for the errors.
+fn solve_display_problem(
+    observation: &DisplayObservations,
+    line_index: usize,
+) -> Result<Display, Error> {
+    let no_solution = |message: &str| {
+        return Error::no_solution(Day08::DAY, format!("line {}: {}", line_index + 1, message));
+    };
+
     // This could probably be implemented in a shorter, faster, and better way
     // but I like to do it step by step in the way I thought about it first.
 
@@ -120,49 +126,49 @@ fn solve_display_problem(observation: &DisplayObservations) -> Display {
         .patterns
         .iter()
         .find(|pattern| pattern.len() == 2)
-        .expect("Could not find pattern with 2 length");
+        .ok_or_else(|| no_solution("Could not find pattern with 2 length"))?;
 
     let c_or_f_1 = pattern_2_length
         .chars()
-        .nth(0)
-        .expect("Could not get first char");
+        .next()
+        .ok_or_else(|| no_solution("Could not get first char"))?;
     let c_or_f_2 = pattern_2_length
         .chars()
         .nth(1)
-        .expect("Could not get second char");
+        .ok_or_else(|| no_solution("Could not get second char"))?;
 
     // find the only pattern with string length of 3
     let pattern_3_length = observation
         .patterns
         .iter()
         .find(|pattern| pattern.len() == 3)
-        .expect("Could not find pattern with 3 length");
+        .ok_or_else(|| no_solution("Could not find pattern with 3 length"))?;
 
     let segment_a = pattern_3_length
         .chars()
         .find(|possibility| *possibility != c_or_f_1 && *possibility != c_or_f_2)
-        .expect("Could not find segment a");
+        .ok_or_else(|| no_solution("Could not find segment a"))?;
 
     // find the only pattern with string length of 4
     let pattern_4_length = observation
         .patterns
         .iter()
         .find(|pattern| pattern.len() == 4)
-        .expect("Could not find pattern with 4 length");
+        .ok_or_else(|| no_solution("Could not find pattern with 4 length"))?;
 
     let b_or_d_1 = pattern_4_length
         .chars()
         .find(|possibility| *possibility != c_or_f_1 && *possibility != c_or_f_2)
-        .expect("Could not get first char");
+        .ok_or_else(|| no_solution("Could not get first char"))?;
 
     let b_or_d_2 = pattern_4_length
         .chars()
         .find(|possibility| {
             *possibility != c_or_f_1 && *possibility != c_or_f_2 && *possibility != b_or_d_1
         })
-        .expect("Could not get second char");
+        .ok_or_else(|| no_solution("Could not get second char"))?;
 
-    let all_letters = vec!['a', 'b', 'c', 'd', 'e', 'f', 'g'];
+    let all_letters = ['a', 'b', 'c', 'd', 'e', 'f', 'g'];
     let two_left_unused_letters = all_letters.iter().filter(|letter| {
         *letter != &c_or_f_1
             && *letter != &c_or_f_2
@@ -173,12 +179,12 @@ fn solve_display_problem(observation: &DisplayObservations) -> Display {
 
     let left_unused_letter_1 = *two_left_unused_letters
         .clone()
-        .nth(0)
-        .expect("Could not get first unused letter");
+        .next()
+        .ok_or_else(|| no_solution("Could not get first unused letter"))?;
     let left_unused_letter_2 = *two_left_unused_letters
         .clone()
         .nth(1)
-        .expect("Could not get second unused letter");
+        .ok_or_else(|| no_solution("Could not get second unused letter"))?;
 
     // find pattern of 5 length with the letter of segment a and the two left unused letters
     let pattern_5_length_1 = observation
@@ -190,7 +196,11 @@ fn solve_display_problem(observation: &DisplayObservations) -> Display {
                 && pattern.contains(left_unused_letter_1)
                 && pattern.contains(left_unused_letter_2)
         })
-        .expect("Could not find pattern with 5 length with segment a and two left unused letters");
+        .ok_or_else(|| {
+            no_solution(
+                "Could not find pattern with 5 length with segment a and two left unused letters",
+            )
+        })?;
 
     let mut segment_c: char = '\0';
     let mut segment_f: char = '\0';
@@ -217,7 +227,7 @@ fn solve_display_problem(observation: &DisplayObservations) -> Display {
         .patterns
         .iter()
         .find(|pattern| pattern.len() == 5 && *pattern != pattern_5_length_1)
-        .expect("Could not find reminding pattern with 5 length");
+        .ok_or_else(|| no_solution("Could not find reminding pattern with 5 length"))?;
 
     let mut segment_e: char = '\0';
     let mut segment_g: char = '\0';
@@ -232,13 +242,12 @@ fn solve_display_problem(observation: &DisplayObservations) -> Display {
     }
 
     // check that any segment is not zero
-    assert!(segment_a != '\0', "Segment a is 0");
-    assert!(segment_b != '\0', "Segment b is 0");
-    assert!(segment_c != '\0', "Segment c is 0");
-    assert!(segment_d != '\0', "Segment d is 0");
-    assert!(segment_e != '\0', "Segment e is 0");
-    assert!(segment_f != '\0', "Segment f is 0");
-    assert!(segment_g != '\0', "Segment g is 0");
+    let segments = [
+        segment_a, segment_b, segment_c, segment_d, segment_e, segment_f, segment_g,
+    ];
+    if segments.contains(&'\0') {
+        return Err(no_solution("Could not find all the segments"));
+    }
 
     /*
      0:      1:      2:      3:      4:
@@ -261,98 +270,121 @@ fn solve_display_problem(observation: &DisplayObservations) -> Display {
     */
     let mut digits: [String; 10] = Default::default();
 
-    let mut digit_0 = vec![
+    let mut digit_0 = [
         segment_a, segment_b, segment_c, segment_e, segment_f, segment_g,
     ];
     digit_0.sort();
     digits[0] = digit_0.iter().collect();
 
-    let mut digit_1 = vec![segment_c, segment_f];
+    let mut digit_1 = [segment_c, segment_f];
     digit_1.sort();
     digits[1] = digit_1.iter().collect();
 
-    let mut digit_2 = vec![segment_a, segment_c, segment_d, segment_e, segment_g];
+    let mut digit_2 = [segment_a, segment_c, segment_d, segment_e, segment_g];
     digit_2.sort();
     digits[2] = digit_2.iter().collect();
 
-    let mut digit_3 = vec![segment_a, segment_c, segment_d, segment_f, segment_g];
+    let mut digit_3 = [segment_a, segment_c, segment_d, segment_f, segment_g];
     digit_3.sort();
     digits[3] = digit_3.iter().collect();
 
-    let mut digit_4 = vec![segment_b, segment_c, segment_d, segment_f];
+    let mut digit_4 = [segment_b, segment_c, segment_d, segment_f];
     digit_4.sort();
     digits[4] = digit_4.iter().collect();
 
-    let mut digit_5 = vec![segment_a, segment_b, segment_d, segment_f, segment_g];
+    let mut digit_5 = [segment_a, segment_b, segment_d, segment_f, segment_g];
     digit_5.sort();
     digits[5] = digit_5.iter().collect();
 
-    let mut digit_6 = vec![
+    let mut digit_6 = [
         segment_a, segment_b, segment_d, segment_e, segment_f, segment_g,
     ];
     digit_6.sort();
     digits[6] = digit_6.iter().collect();
 
-    let mut digit_7 = vec![segment_a, segment_c, segment_f];
+    let mut digit_7 = [segment_a, segment_c, segment_f];
     digit_7.sort();
     digits[7] = digit_7.iter().collect();
 
-    let mut digit_8 = vec![
+    let mut digit_8 = [
         segment_a, segment_b, segment_c, segment_d, segment_e, segment_f, segment_g,
     ];
     digit_8.sort();
     digits[8] = digit_8.iter().collect();
 
-    let mut digit_9 = vec![
+    let mut digit_9 = [
         segment_a, segment_b, segment_c, segment_d, segment_f, segment_g,
     ];
     digit_9.sort();
     digits[9] = digit_9.iter().collect();
 
-    return Display { digits };
+    // The segments were found with only some of the patterns, the others have to agree
+    for pattern in &observation.patterns {
+        if !digits.contains(&sorted(pattern)) {
+            return Err(no_solution(&format!("{} is not a digit", pattern)));
+        }
+    }
+
+    return Ok(Display { digits });
+}
+
+fn sorted(pattern: &str) -> String {
+    let mut chars: Vec<char> = pattern.chars().collect();
+    chars.sort();
+    return chars.into_iter().collect();
+}
+
+fn convert_output_to_digit(output: &str, display: &Display) -> Option<i64> {
+    let output = sorted(output);
+    return display
+        .digits
+        .iter()
+        .position(|digit| *digit == output)
+        .map(|digit| digit as i64);
 }
 
-fn convert_output_to_digit(output: &str, display: &Display) -> i64 {
-    // sort output &str chars in ascii order
-    let mut output_chars: Vec<char> = output.chars().collect();
-    output_chars.sort();
-    // convert sorted output &str chars to string
-    let output_string: String = output_chars.into_iter().collect();
-
-    // find digit in display
-    for (index, digit) in display.digits.iter().enumerate() {
-        if output_string == *digit {
-            return index as i64;
+fn sum_of_outputs(observations: &[DisplayObservations]) -> Result<i64, Error> {
+    let mut sum = 0;
+    for (line_index, observation) in observations.iter().enumerate() {
+        let display = solve_display_problem(observation, line_index)?;
+        let mut value = 0;
+        for output in &observation.outputs {
+            let digit = convert_output_to_digit(output, &display).ok_or_else(|| {
+                Error::no_solution(
+                    Day08::DAY,
+                    format!(
+                        "line {}: the output {} is not a digit",
+                        line_index + 1,
+                        output
+                    ),
+                )
+            })?;
+            value = value * 10 + digit;
         }
+        sum += value;
     }
-    return 0;
+    return Ok(sum);
 }
 
-pub fn day_8_part_2(data: &str) -> i64 {
-    let observations = parse_data(data);
-    return observations
-        .iter()
-        .map(|observation| {
-            let display = solve_display_problem(observation);
-            let first_digit = convert_output_to_digit(
-                observation.outputs.get(0).expect("Could not get output"),
-                &display,
-            );
-            let second_digit = convert_output_to_digit(
-                observation.outputs.get(1).expect("Could not get output"),
-                &display,
-            );
-            let third_digit = convert_output_to_digit(
-                observation.outputs.get(2).expect("Could not get output"),
-                &display,
-            );
-            let fourth_digit = convert_output_to_digit(
-                observation.outputs.get(3).expect("Could not get output"),
-                &display,
-            );
-            return first_digit * 1000 + second_digit * 100 + third_digit * 10 + fourth_digit;
-        })
-        .sum();
+pub struct Day08;
+
+impl Solution for Day08 {
+    const DAY: u8 = 8;
+    const TITLE: &'static str = "Seven Segment Search";
+
+    type Input<'a> = Vec<DisplayObservations>;
+
+    fn parse(data: &str) -> Result<Vec<DisplayObservations>, ParseError> {
+        return parse_data(data);
+    }
+
+    fn part_1(observations: &Vec<DisplayObservations>) -> Result<i64, Error> {
+        return Ok(count_easy_digits(observations));
+    }
+
+    fn part_2(observations: &Vec<DisplayObservations>) -> Result<i64, Error> {
+        return sum_of_outputs(observations);
+    }
 }
 
 #[cfg(test)]
@@ -375,13 +407,52 @@ gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce
 
     #[test]
     fn test_day_8_part_1() {
-        assert_eq!(day_8_part_1(EXAMPLE_1), 0);
-        assert_eq!(day_8_part_1(EXAMPLE_2), 26);
+        assert_eq!(Day08::solve_part_1(EXAMPLE_1), Ok(0));
+        assert_eq!(Day08::solve_part_1(EXAMPLE_2), Ok(26));
     }
 
     #[test]
     fn test_day_8_part_2() {
-        assert_eq!(day_8_part_2(EXAMPLE_1), 5353);
-        assert_eq!(day_8_part_2(EXAMPLE_2), 61229);
+        assert_eq!(Day08::solve_part_2(EXAMPLE_1), Ok(5353));
+        assert_eq!(Day08::solve_part_2(EXAMPLE_2), Ok(61229));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_data("acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadz")
+                .unwrap_err(),
+            ParseError::new(8, 1, 72, "z", "segments are letters from a to g")
+        );
+        assert_eq!(
+            parse_data("acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab").unwrap_err(),
+            ParseError::new(8, 1, 59, "", "expected a |")
+        );
+    }
+
+    #[test]
+    fn test_not_digits() {
+        assert_eq!(
+            Day08::solve_part_2("ab ab ab ab ab ab ab ab ab ab | ab ab ab ab"),
+            Err(Error::no_solution(
+                8,
+                "line 1: Could not find pattern with 3 length"
+            ))
+        );
+        // Every length is there, but the last pattern is not a 9
+        assert_eq!(
+            Day08::solve_part_2(
+                "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf\n\
+                 acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf\n\
+                 acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagefb ab | cdfeb fcadb cdfeb cdbaf"
+            ),
+            Err(Error::no_solution(8, "line 3: cagefb is not a digit"))
+        );
+        assert_eq!(
+            Day08::solve_part_2(
+                "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdb"
+            ),
+            Err(Error::no_solution(8, "line 1: the output cdb is not a digit"))
+        );
     }
 }
@@ -1,36 +1,21 @@
-use ndarray::Array2;
 use std::collections::BinaryHeap;
 
-fn parse_data(data: &str) -> Array2<u8> {
-    let data: Vec<Vec<u8>> = data
-        .lines()
-        .into_iter()
-        .map(|x| {
-            x.as_bytes()
-                .iter()
-                .map(|&x| u8::from(x - 48))
-                .collect::<Vec<u8>>()
-        })
-        .collect();
-
-    let shape = (data.len(), data[0].len());
-    let flat_data = data.into_iter().flatten().collect::<Vec<u8>>();
+use crate::error::{Error, ParseError};
+use crate::grid::Grid;
+use crate::solution::Solution;
 
-    return Array2::from_shape_vec(shape, flat_data).expect("Could not create array from data");
+pub fn parse_data(data: &str) -> Result<Grid<u8>, ParseError> {
+    return Grid::from_digits(Day09::DAY, data, "heights");
 }
 
-pub fn day_9_part_1(data: &str) -> i64 {
-    let data = parse_data(data);
-    let dim = data.dim();
-
+fn sum_of_low_points_risk(data: &Grid<u8>) -> i64 {
     return data
         .indexed_iter()
-        .map(|((i, j), location)| {
+        .map(|(point, location)| {
             // If a neigbour location is higher
-            if (i > 0 && data[(i - 1, j)] <= *location)
-                || (i < dim.0 - 1 && data[(i + 1, j)] <= *location)
-                || (j > 0 && data[(i, j - 1)] <= *location)
-                || (j < dim.1 - 1 && data[(i, j + 1)] <= *location)
+            if data
+                .neighbours_4(point)
+                .any(|neighbour| data[neighbour] <= *location)
             {
                 return 0;
             }
@@ -40,33 +25,31 @@ pub fn day_9_part_1(data: &str) -> i64 {
         .sum();
 }
 
-pub fn day_9_part_2(data: &str) -> i64 {
-    let data = parse_data(data);
-    let dim = data.dim();
-    let mut basins: Array2<usize> = Array2::zeros(dim);
+fn product_of_largest_basins(data: &Grid<u8>) -> i64 {
+    let mut basins: Grid<usize> = Grid::new(data.width(), data.height(), 0);
 
     // I could use a recursive function but I want to play with heap data structures now
     // to avoid stack overflows during the next days.
     let mut heap: BinaryHeap<(usize, (usize, usize))> = BinaryHeap::new();
 
     // Make sure that we visit all the locations by adding them in the heap
-    for ((i, j), location) in data.indexed_iter() {
+    for (point, location) in data.indexed_iter() {
         // The 9 are some kind of walls that we ignore
         if *location != 9 {
-            heap.push((0, (i, j)));
+            heap.push((0, point));
         }
     }
 
     // Create a map with the size of the maximum number of basins
     // Basin identifiers start from 1, so we ignore the index 0
-    let mut basins_counters: Vec<usize> = vec![0; dim.0 * dim.1 + 1];
+    let mut basins_counters: Vec<usize> = vec![0; data.len() + 1];
 
     let mut nb_basins = 0;
 
     // Iterate until the heap is not empty
-    while let Some((basin_id, (i, j))) = heap.pop() {
+    while let Some((basin_id, point)) = heap.pop() {
         // If the location is already part of a basin we ignore it
-        if basins[(i, j)] != 0 {
+        if basins[point] != 0 {
             continue;
         }
 
@@ -78,28 +61,21 @@ pub fn day_9_part_2(data: &str) -> i64 {
         }
 
         // Debugging with println is sometimes nicer than the debugger
-        //println!("{:?}", ((i, j), basin_id, new_basin_id));
+        //println!("{:?}", (point, basin_id, new_basin_id));
         //println!("{:?}", basins);
         //println!("----");
 
         // Mark the location as part of a basin
-        basins[(i, j)] = current_basin_id;
+        basins[point] = current_basin_id;
 
         // Increment the counter for the basin
         basins_counters[current_basin_id] += 1;
 
         // Check the neighbours
-        if i > 0 && data[(i - 1, j)] != 9 && basins[(i - 1, j)] == 0 {
-            heap.push((current_basin_id, (i - 1, j)));
-        }
-        if i < dim.0 - 1 && data[(i + 1, j)] != 9 && basins[(i + 1, j)] == 0 {
-            heap.push((current_basin_id, (i + 1, j)));
-        }
-        if j > 0 && data[(i, j - 1)] != 9 && basins[(i, j - 1)] == 0 {
-            heap.push((current_basin_id, (i, j - 1)));
-        }
-        if j < dim.1 - 1 && data[(i, j + 1)] != 9 && basins[(i, j + 1)] == 0 {
-            heap.push((current_basin_id, (i, j + 1)));
+        for neighbour in data.neighbours_4(point) {
+            if data[neighbour] != 9 && basins[neighbour] == 0 {
+                heap.push((current_basin_id, neighbour));
+            }
         }
     }
 
@@ -117,6 +93,27 @@ pub fn day_9_part_2(data: &str) -> i64 {
         .product::<i64>();
 }
 
+pub struct Day09;
+
+impl Solution for Day09 {
+    const DAY: u8 = 9;
+    const TITLE: &'static str = "Smoke Basin";
+
+    type Input<'a> = Grid<u8>;
+
+    fn parse(data: &str) -> Result<Grid<u8>, ParseError> {
+        return parse_data(data);
+    }
+
+    fn part_1(data: &Grid<u8>) -> Result<i64, Error> {
+        return Ok(sum_of_low_points_risk(data));
+    }
+
+    fn part_2(data: &Grid<u8>) -> Result<i64, Error> {
+        return Ok(product_of_largest_basins(data));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -129,11 +126,23 @@ mod tests {
 
     #[test]
     fn test_day_9_part_1() {
-        assert_eq!(day_9_part_1(EXAMPLE), 15);
+        assert_eq!(Day09::solve_part_1(EXAMPLE), Ok(15));
     }
 
     #[test]
     fn test_day_9_part_2() {
-        assert_eq!(day_9_part_2(EXAMPLE), 1134);
+        assert_eq!(Day09::solve_part_2(EXAMPLE), Ok(1134));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_data("2199\n39.7").unwrap_err(),
+            ParseError::new(9, 2, 3, ".", "heights are digits")
+        );
+        assert_eq!(
+            parse_data("2199\n397").unwrap_err(),
+            ParseError::new(9, 2, 1, "397", "expected 4 heights")
+        );
     }
 }
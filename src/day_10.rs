@@ -1,7 +1,10 @@
 use std::str::Chars;
 
+use crate::error::{Error, ParseError};
+use crate::solution::Solution;
+
 // Return the error score, 0 means no error
-fn compute_error_score(instructions: Chars) -> (i64, Option<Vec<char>>) {
+pub fn compute_error_score(instructions: Chars) -> (i64, Option<Vec<char>>) {
     let mut stack: Vec<char> = Vec::new();
     for instruction in instructions {
         match instruction {
@@ -27,7 +30,7 @@ fn compute_error_score(instructions: Chars) -> (i64, Option<Vec<char>>) {
     return (0, Some(stack));
 }
 
-fn compute_autocomplete_score(stack: Vec<char>) -> i64 {
+pub fn compute_autocomplete_score(stack: Vec<char>) -> i64 {
     let mut score: i64 = 0;
     for instruction in stack.iter().rev() {
         match instruction {
@@ -41,16 +44,16 @@ fn compute_autocomplete_score(stack: Vec<char>) -> i64 {
     return score;
 }
 
-pub fn day_10_part_1(data: &str) -> i64 {
-    return data
-        .lines()
+fn total_syntax_error_score(lines: &[&str]) -> i64 {
+    return lines
+        .iter()
         .map(|line| compute_error_score(line.chars()).0)
         .sum();
 }
 
-pub fn day_10_part_2(data: &str) -> i64 {
-    let mut scores: Vec<i64> = data
-        .lines()
+fn middle_autocomplete_score(lines: &[&str]) -> Result<i64, Error> {
+    let mut scores: Vec<i64> = lines
+        .iter()
         .map(|line| {
             let (_, stack) = compute_error_score(line.chars());
             if let Some(stack) = stack {
@@ -60,9 +63,49 @@ pub fn day_10_part_2(data: &str) -> i64 {
         })
         .filter(|score| *score != 0)
         .collect();
+    if scores.is_empty() {
+        return Err(Error::no_solution(Day10::DAY, "No incomplete line"));
+    }
     scores.sort_unstable();
     // get the middle score element
-    return scores[scores.len() / 2];
+    return Ok(scores[scores.len() / 2]);
+}
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+    const TITLE: &'static str = "Syntax Scoring";
+
+    type Input<'a> = Vec<&'a str>;
+
+    fn parse(data: &str) -> Result<Vec<&str>, ParseError> {
+        return data
+            .lines()
+            .enumerate()
+            .map(|(line_index, line)| {
+                if let Some(index) = line.find(|c| !"()[]{}<>".contains(c)) {
+                    let c = line[index..].chars().next().unwrap_or(' ');
+                    return Err(ParseError::at_token(
+                        Day10::DAY,
+                        line_index,
+                        line,
+                        &line[index..index + c.len_utf8()],
+                        "expected a bracket",
+                    ));
+                }
+                return Ok(line);
+            })
+            .collect();
+    }
+
+    fn part_1(lines: &Vec<&str>) -> Result<i64, Error> {
+        return Ok(total_syntax_error_score(lines));
+    }
+
+    fn part_2(lines: &Vec<&str>) -> Result<i64, Error> {
+        return middle_autocomplete_score(lines);
+    }
 }
 
 #[cfg(test)]
@@ -82,12 +125,20 @@ mod tests {
 
     #[test]
     fn test_day_10_part_1() {
-        assert_eq!(day_10_part_1(EXAMPLE), 26397);
+        assert_eq!(Day10::solve_part_1(EXAMPLE), Ok(26397));
     }
 
     #[test]
     fn test_day_10_part_2() {
-        assert_eq!(day_10_part_2(EXAMPLE), 288957);
+        assert_eq!(Day10::solve_part_2(EXAMPLE), Ok(288957));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Day10::parse("[({(<(())[]>[[{[]{<()<>>\n[(()[<>])]({[<{<<a]>>(").unwrap_err(),
+            ParseError::new(10, 2, 18, "a", "expected a bracket")
+        );
     }
 
     #[test]
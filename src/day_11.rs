@@ -1,82 +1,247 @@
-use std::ops::AddAssign;
+use crate::error::{Error, ParseError};
+use crate::grid::{Edges, Grid};
+use crate::solution::Solution;
 
-use ndarray::Array2;
+// Any rectangular grid of digits, the puzzle inputs are 10x10
+pub fn parse_data(data: &str) -> Result<Grid<u8>, ParseError> {
+    return Grid::from_digits(Day11::DAY, data, "energy levels");
+}
 
-fn parse_data(data: &str) -> Array2<i64> {
-    let numbers = data
-        .chars()
-        .filter(|c| c.is_digit(10))
-        .map(|c| c.to_digit(10).unwrap() as i64)
-        .collect::<Vec<i64>>();
+/**
+ * An octopus flashing during a step. The ones above 9 after the increase
+ * are the wave 0, the ones they made flash are the wave 1, and so on.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Flash {
+    pub point: (usize, usize),
+    pub wave: usize,
+}
 
-    if numbers.len() != 100 {
-        panic!("Invalid input, not 100 digits");
+// One step of the simulation, returns the flashes in the order they happened
+pub fn step(data: &mut Grid<u8>, edges: Edges) -> Vec<Flash> {
+    let mut flashes: Vec<Flash> = vec![];
+    for point in data.points() {
+        data[point] += 1;
+        if data[point] > 9 {
+            flashes.push(Flash { point, wave: 0 });
+        }
     }
 
-    return Array2::from_shape_vec((10, 10), numbers).expect("Could not create 2d array");
-}
-
-fn step(data: &mut Array2<i64>) -> i64 {
-    data.add_assign(1);
-    let mut did_boom = false;
-    let mut nb_booms: i64 = 0;
-    loop {
-        for i in 0..10 {
-            for j in 0..10 {
-                let energy = data[(i, j)];
-                // If a boom is detected
-                if energy > 9 {
-                    // Use a large negative number to mark the cell as a boom
-                    // The idea is to not have them explode twice in the step
-                    data[(i, j)] = -0xDEADBEEF;
-                    did_boom = true;
-                    nb_booms += 1;
-
-                    // Increment all neirbor cells, including diagonals and itself because why not
-                    // Check if they are within the boundaries of the 10x10 grid and not negative
-                    let i_min = if i > 0 { i - 1 } else { 0 };
-                    let j_min = if j > 0 { j - 1 } else { 0 };
-                    let i_max = if i < 9 { i + 1 } else { 9 };
-                    let j_max = if j < 9 { j + 1 } else { 9 };
-                    for x in i_min..=i_max {
-                        for y in j_min..=j_max {
-                            data[(x, y)] += 1;
-                        }
-                    }
-                }
+    // The flashes are propagated wave by wave, without scanning the grid again.
+    // The flashes are also the queue, and an octopus flashes at most once per
+    // step: the ones above 9 already did, so they don't need another grid.
+    let mut next = 0;
+    while next < flashes.len() {
+        let flash = flashes[next];
+        next += 1;
+        for neighbour in data.neighbours_8_with(flash.point, edges) {
+            if data[neighbour] > 9 {
+                continue;
             }
-        }
-        if !did_boom {
-            // Time to cleanup the cells that exploded (back to 0)
-            for energy in data.iter_mut() {
-                if *energy < 0 {
-                    *energy = 0;
-                }
+            data[neighbour] += 1;
+            if data[neighbour] > 9 {
+                flashes.push(Flash {
+                    point: neighbour,
+                    wave: flash.wave + 1,
+                });
             }
-            break;
         }
-        did_boom = false;
     }
-    return nb_booms;
+
+    for flash in &flashes {
+        data[flash.point] = 0;
+    }
+    return flashes;
 }
 
-pub fn day_11_part_1(data: &str) -> i64 {
-    let mut data = parse_data(data);
+pub fn count_flashes(data: &Grid<u8>, steps: usize, edges: Edges) -> i64 {
+    let mut data = data.clone();
     let mut nb_booms = 0;
-    for _i in 0..100 {
-        nb_booms += step(&mut data);
+    for _i in 0..steps {
+        nb_booms += step(&mut data, edges).len() as i64;
     }
     return nb_booms;
 }
 
-pub fn day_11_part_2(data: &str) -> i64 {
-    let mut data = parse_data(data);
-    for step_id in 1..=10000 {
-        if step(&mut data) == 100 {
+/**
+ * The flashes of every step, forever.
+ *
+ * There is a finite number of grids, so the simulation always ends up in a
+ * cycle. It's found with Floyd's tortoise and hare, so only a few grids are
+ * kept instead of all of them, and the steps after that are found in the
+ * cycle instead of being simulated.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlashHistory {
+    // totals[i] is the number of flashes during the first i steps
+    totals: Vec<u64>,
+    // The steps after cycle_start repeat every cycle_length steps
+    pub cycle_start: u64,
+    pub cycle_length: u64,
+    octopuses: u64,
+}
+
+impl FlashHistory {
+    // Gives up when there is no cycle in the first max_steps steps
+    pub fn new(data: &Grid<u8>, edges: Edges, max_steps: u64) -> Result<Self, Error> {
+        let no_cycle = || {
+            return Error::no_solution(
+                Day11::DAY,
+                format!("No cycle in the first {} steps", max_steps),
+            );
+        };
+
+        // The hare goes twice as fast, they meet after i steps with i a multiple
+        // of the cycle length, and i <= cycle_start + cycle_length.
+        // The hare is the first one on every step, it keeps the totals.
+        let mut tortoise = data.clone();
+        let mut hare = data.clone();
+        let mut totals: Vec<u64> = vec![0];
+        let mut meeting_step = 0;
+        loop {
+            meeting_step += 1;
+            if meeting_step > max_steps {
+                return Err(no_cycle());
+            }
+            step(&mut tortoise, edges);
+            for _ in 0..2 {
+                let flashes = step(&mut hare, edges).len() as u64;
+                totals.push(totals[totals.len() - 1] + flashes);
+            }
+            if tortoise == hare {
+                break;
+            }
+        }
+
+        // Going from the start and from the meeting grid at the same speed,
+        // they meet at the start of the cycle
+        let mut hare = tortoise;
+        let mut tortoise = data.clone();
+        let mut cycle_start = 0;
+        while tortoise != hare {
+            step(&mut tortoise, edges);
+            step(&mut hare, edges);
+            cycle_start += 1;
+        }
+
+        let mut cycle_length = 1;
+        step(&mut hare, edges);
+        while tortoise != hare {
+            step(&mut hare, edges);
+            cycle_length += 1;
+        }
+
+        // Same as stopping at the first grid that comes back
+        if cycle_start + cycle_length > max_steps {
+            return Err(no_cycle());
+        }
+        totals.truncate((cycle_start + cycle_length) as usize + 1);
+        return Ok(FlashHistory {
+            totals,
+            cycle_start,
+            cycle_length,
+            octopuses: data.len() as u64,
+        });
+    }
+
+    // The step with the same flashes, among the simulated ones
+    fn simulated_step(&self, step_id: u64) -> u64 {
+        if step_id <= self.cycle_start {
             return step_id;
         }
+        return self.cycle_start + 1 + (step_id - self.cycle_start - 1) % self.cycle_length;
+    }
+
+    // Flashes during a step, the first step is 1
+    pub fn flashes_at(&self, step_id: u64) -> u64 {
+        if step_id == 0 {
+            return 0;
+        }
+        let step_id = self.simulated_step(step_id) as usize;
+        return self.totals[step_id] - self.totals[step_id - 1];
+    }
+
+    // Flashes during the first steps, in u128 as u64 overflows after 10^17 steps of a 10x10 grid
+    pub fn total_flashes(&self, steps: u64) -> u128 {
+        if steps <= self.cycle_start {
+            return u128::from(self.totals[steps as usize]);
+        }
+        let start = self.cycle_start as usize;
+        let cycle_flashes = self.totals[start + self.cycle_length as usize] - self.totals[start];
+        let cycles = (steps - self.cycle_start) / self.cycle_length;
+        let remaining = (steps - self.cycle_start) % self.cycle_length;
+        return u128::from(self.totals[start])
+            + u128::from(cycles) * u128::from(cycle_flashes)
+            + u128::from(self.totals[start + remaining as usize] - self.totals[start]);
+    }
+
+    // The steps up to `last` when all the octopuses flash, in order
+    pub fn synchronised_steps(&self, last: u64) -> impl Iterator<Item = u64> + '_ {
+        let before_cycle = (1..=last.min(self.cycle_start))
+            .filter(move |&step_id| self.flashes_at(step_id) == self.octopuses);
+
+        // The synchronised steps of the cycle, as offsets from its first step
+        let offsets = (0..self.cycle_length)
+            .filter(|&offset| self.flashes_at(self.cycle_start + 1 + offset) == self.octopuses)
+            .collect::<Vec<u64>>();
+        let cycles = if offsets.is_empty() || last <= self.cycle_start {
+            0
+        } else {
+            (last - self.cycle_start - 1) / self.cycle_length + 1
+        };
+        let (start, length) = (self.cycle_start, self.cycle_length);
+        let in_cycles = (0..cycles)
+            .flat_map(move |cycle| {
+                let offsets = offsets.clone();
+                return offsets
+                    .into_iter()
+                    .map(move |offset| start + 1 + cycle * length + offset);
+            })
+            .take_while(move |&step_id| step_id <= last);
+
+        return before_cycle.chain(in_cycles);
+    }
+
+    pub fn first_synchronised_step(&self) -> Option<u64> {
+        return self.synchronised_steps(u64::MAX).next();
+    }
+}
+
+// The first step when all the octopuses flash, if they ever do
+pub fn first_synchronised_step(
+    data: &Grid<u8>,
+    edges: Edges,
+    max_steps: u64,
+) -> Result<i64, Error> {
+    let history = FlashHistory::new(data, edges, max_steps)?;
+    return match history.first_synchronised_step() {
+        Some(step_id) => Ok(step_id as i64),
+        None => Err(Error::no_solution(
+            Day11::DAY,
+            "The octopuses never all flash at the same time",
+        )),
+    };
+}
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+    const TITLE: &'static str = "Dumbo Octopus";
+
+    type Input<'a> = Grid<u8>;
+
+    fn parse(data: &str) -> Result<Grid<u8>, ParseError> {
+        return parse_data(data);
+    }
+
+    fn part_1(data: &Grid<u8>) -> Result<i64, Error> {
+        return Ok(count_flashes(data, 100, Edges::Bounded));
+    }
+
+    fn part_2(data: &Grid<u8>) -> Result<i64, Error> {
+        return first_synchronised_step(data, Edges::Bounded, 1_000_000);
     }
-    panic!("Never all blinked");
 }
 
 #[cfg(test)]
@@ -96,11 +261,106 @@ mod tests {
 
     #[test]
     fn test_day_11_part_1() {
-        assert_eq!(day_11_part_1(EXAMPLE), 1656);
+        assert_eq!(Day11::solve_part_1(EXAMPLE), Ok(1656));
     }
 
     #[test]
     fn test_day_11_part_2() {
-        assert_eq!(day_11_part_2(EXAMPLE), 195);
+        assert_eq!(Day11::solve_part_2(EXAMPLE), Ok(195));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_data("54831432x3").unwrap_err(),
+            ParseError::new(11, 1, 9, "x", "energy levels are digits")
+        );
+        assert_eq!(
+            parse_data("5483143223\n274585471").unwrap_err(),
+            ParseError::new(11, 2, 1, "274585471", "expected 10 energy levels")
+        );
+    }
+
+    #[test]
+    fn test_flash_events() {
+        let mut data = parse_data("11111\n19991\n19191\n19991\n11111").unwrap();
+        let flashes = step(&mut data, Edges::Bounded);
+        assert_eq!(data.to_string(), "34543\n40004\n50005\n40004\n34543");
+        assert_eq!(flashes.len(), 9);
+        assert!(flashes[..8].iter().all(|flash| flash.wave == 0));
+        assert_eq!(flashes[0].point, (1, 1));
+        assert_eq!(
+            flashes[8],
+            Flash {
+                point: (2, 2),
+                wave: 1
+            }
+        );
+
+        assert_eq!(step(&mut data, Edges::Bounded), vec![]);
+        assert_eq!(data.to_string(), "45654\n51115\n61116\n51115\n45654");
+    }
+
+    #[test]
+    fn test_any_grid_size() {
+        let mut data = parse_data("9000").unwrap();
+        assert_eq!(step(&mut data, Edges::Bounded).len(), 1);
+        assert_eq!(data.to_string(), "0211");
+
+        // The first octopus is a neighbour of the last one
+        let mut data = parse_data("9000").unwrap();
+        assert_eq!(step(&mut data, Edges::Wrapping).len(), 1);
+        assert_eq!(data.to_string(), "0212");
+
+        let data = parse_data("999\n999").unwrap();
+        assert_eq!(first_synchronised_step(&data, Edges::Bounded, 10), Ok(1));
+        assert_eq!(count_flashes(&data, 2, Edges::Wrapping), 6);
+        assert_eq!(
+            first_synchronised_step(&parse_data("90").unwrap(), Edges::Bounded, 10),
+            Err(Error::no_solution(
+                11,
+                "The octopuses never all flash at the same time"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_flash_history() {
+        let data = parse_data(EXAMPLE).unwrap();
+        let history = FlashHistory::new(&data, Edges::Bounded, 1000).unwrap();
+        // Once synchronised, they all flash every 10 steps
+        assert_eq!((history.cycle_start, history.cycle_length), (195, 10));
+        assert_eq!(history.total_flashes(10), 204);
+        assert_eq!(history.total_flashes(100), 1656);
+        assert_eq!(history.flashes_at(195), 100);
+        assert_eq!(history.flashes_at(196), 0);
+        assert_eq!(
+            history.synchronised_steps(225).collect::<Vec<u64>>(),
+            [195, 205, 215, 225]
+        );
+        assert_eq!(history.first_synchronised_step(), Some(195));
+
+        // Checked against the simulation, and then far away
+        let steps = 1234;
+        assert_eq!(
+            history.total_flashes(steps),
+            count_flashes(&data, steps as usize, Edges::Bounded) as u128
+        );
+        assert_eq!(history.flashes_at(1_000_000_000_005), 100);
+        assert_eq!(
+            history.total_flashes(1_000_000_000_000),
+            history.total_flashes(195) + (1_000_000_000_000 - 195) / 10 * 100
+        );
+        assert_eq!(history.synchronised_steps(u64::MAX).nth(10), Some(295));
+        assert_eq!(
+            history.total_flashes(u64::MAX),
+            history.total_flashes(195) + (u128::from(u64::MAX) - 195) / 10 * 100
+        );
+
+        // Just enough steps to see the grid of step 195 again at step 205
+        assert!(FlashHistory::new(&data, Edges::Bounded, 205).is_ok());
+        assert!(FlashHistory::new(&data, Edges::Bounded, 204).is_err());
+
+        assert!(FlashHistory::new(&data, Edges::Bounded, 100).is_err());
     }
 }
@@ -1,5 +1,8 @@
 use std::collections::HashSet;
 
+use crate::error::{Error, ParseError};
+use crate::solution::Solution;
+
 /**
  * Making a Graph in Rust is actually not trivial like in many other programming languages.
  *
@@ -18,19 +21,45 @@ fn is_string_only_uppercase(data: &str) -> bool {
     data.chars().all(|c| c.is_ascii_uppercase())
 }
 
-type Caves<'a> = UnGraphMap<&'a str, ()>;
-
-fn parse_data(data: &str) -> Caves {
-    let caves: UnGraphMap<&str, ()> = UnGraphMap::from_edges(data.lines().map(|line| {
-        let mut raw_connection = line.split('-');
-        let link_start = raw_connection.next().expect("No start link");
-        let link_end = raw_connection.next().expect("No end link");
-        return (link_start, link_end);
-    }));
+pub type Caves<'a> = UnGraphMap<&'a str, ()>;
+
+pub fn parse_data(data: &str) -> Result<Caves<'_>, ParseError> {
+    let connections = data
+        .lines()
+        .enumerate()
+        .map(|(line_index, line)| {
+            let (link_start, link_end) = line.split_once('-').ok_or_else(|| {
+                ParseError::at_token(Day12::DAY, line_index, line, line, "expected cave-cave")
+            })?;
+            for cave in [link_start, link_end] {
+                if cave.is_empty() || !cave.chars().all(|c| c.is_ascii_alphabetic()) {
+                    return Err(ParseError::at_token(
+                        Day12::DAY,
+                        line_index,
+                        line,
+                        cave,
+                        "invalid cave name",
+                    ));
+                }
+            }
+            // There would be an infinite number of paths going back and forth
+            if is_string_only_uppercase(link_start) && is_string_only_uppercase(link_end) {
+                return Err(ParseError::at_token(
+                    Day12::DAY,
+                    line_index,
+                    line,
+                    line,
+                    "two big caves can't be connected",
+                ));
+            }
+            return Ok((link_start, link_end));
+        })
+        .collect::<Result<Vec<(&str, &str)>, ParseError>>()?;
+    let caves: UnGraphMap<&str, ()> = UnGraphMap::from_edges(connections);
 
     // Print petgraph in dot format
     // println!("{:?}", petgraph::dot::Dot::new(&caves));
-    return caves;
+    return Ok(caves);
 }
 
 fn count_paths_deep<'a>(caves: &Caves<'a>, current: &'a str, visited: HashSet<&'a str>) -> usize {
@@ -86,14 +115,25 @@ fn count_paths_deep_twice<'a>(
     return count;
 }
 
-pub fn day_12_part_1(data: &str) -> i64 {
-    let caves = parse_data(data);
-    return count_paths_deep(&caves, "start", HashSet::new()) as i64;
-}
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+    const TITLE: &'static str = "Passage Pathing";
+
+    type Input<'a> = Caves<'a>;
 
-pub fn day_12_part_2(data: &str) -> i64 {
-    let caves = parse_data(data);
-    return count_paths_deep_twice(&caves, "start", HashSet::new()) as i64;
+    fn parse(data: &str) -> Result<Caves<'_>, ParseError> {
+        return parse_data(data);
+    }
+
+    fn part_1(caves: &Caves) -> Result<i64, Error> {
+        return Ok(count_paths_deep(caves, "start", HashSet::new()) as i64);
+    }
+
+    fn part_2(caves: &Caves) -> Result<i64, Error> {
+        return Ok(count_paths_deep_twice(caves, "start", HashSet::new()) as i64);
+    }
 }
 
 #[cfg(test)]
@@ -140,15 +180,31 @@ start-RW";
 
     #[test]
     fn test_day_12_part_1() {
-        assert_eq!(day_12_part_1(SMALL_EXAMPLE), 10);
-        assert_eq!(day_12_part_1(LARGER_EXAMPLE), 19);
-        assert_eq!(day_12_part_1(EVEN_LARGER_EXAMPLE), 226);
+        assert_eq!(Day12::solve_part_1(SMALL_EXAMPLE), Ok(10));
+        assert_eq!(Day12::solve_part_1(LARGER_EXAMPLE), Ok(19));
+        assert_eq!(Day12::solve_part_1(EVEN_LARGER_EXAMPLE), Ok(226));
     }
 
     #[test]
     fn test_day_12_part_2() {
-        assert_eq!(day_12_part_2(SMALL_EXAMPLE), 36);
-        assert_eq!(day_12_part_2(LARGER_EXAMPLE), 103);
-        assert_eq!(day_12_part_2(EVEN_LARGER_EXAMPLE), 3509);
+        assert_eq!(Day12::solve_part_2(SMALL_EXAMPLE), Ok(36));
+        assert_eq!(Day12::solve_part_2(LARGER_EXAMPLE), Ok(103));
+        assert_eq!(Day12::solve_part_2(EVEN_LARGER_EXAMPLE), Ok(3509));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_data("start-A\nstart b").unwrap_err(),
+            ParseError::new(12, 2, 1, "start b", "expected cave-cave")
+        );
+        assert_eq!(
+            parse_data("start-A\nA-").unwrap_err(),
+            ParseError::new(12, 2, 3, "", "invalid cave name")
+        );
+        assert_eq!(
+            parse_data("start-A\nA-end\nA-B").unwrap_err(),
+            ParseError::new(12, 3, 1, "A-B", "two big caves can't be connected")
+        );
     }
 }
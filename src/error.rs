@@ -0,0 +1,213 @@
+use std::fmt;
+use std::str::FromStr;
+
+/**
+ * An error in a puzzle input, with its location.
+ *
+ * Lines and columns start at 1, and columns are counted in characters.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: u8,
+    pub line: usize,
+    pub column: usize,
+    pub token: String,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(
+        day: u8,
+        line: usize,
+        column: usize,
+        token: &str,
+        message: impl Into<String>,
+    ) -> Self {
+        return ParseError {
+            day,
+            line,
+            column,
+            token: token.to_string(),
+            message: message.into(),
+        };
+    }
+
+    // line_index starts at 0 like in .lines().enumerate(),
+    // and token must be a slice of line to find its column.
+    pub fn at_token(
+        day: u8,
+        line_index: usize,
+        line: &str,
+        token: &str,
+        message: impl Into<String>,
+    ) -> Self {
+        return ParseError::new(day, line_index + 1, column_of(line, token), token, message);
+    }
+
+    // For things missing at the end of a line
+    pub fn at_end_of_line(
+        day: u8,
+        line_index: usize,
+        line: &str,
+        message: impl Into<String>,
+    ) -> Self {
+        return ParseError::new(day, line_index + 1, line.chars().count() + 1, "", message);
+    }
+
+    /**
+     * Render the error like a compiler would, with the line of the input
+     * and a caret under the bad token:
+     *
+     * ```text
+     * error: day 5: invalid x coordinate
+     *  --> inputs/day_05.txt:3:8
+     *   |
+     * 3 | 9,4 -> x,4
+     *   |        ^
+     * ```
+     */
+    pub fn render(&self, source: &str, data: &str) -> String {
+        let line_number = self.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+        let line = data.lines().nth(self.line - 1).unwrap_or("");
+        let carets = "^".repeat(self.token.chars().count().max(1));
+        // Keep the tabs so the caret is aligned with the token
+        let padding: String = line
+            .chars()
+            .take(self.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        return format!(
+            "error: day {}: {}\n{}--> {}:{}:{}\n{} |\n{} | {}\n{} | {}{}",
+            self.day,
+            self.message,
+            gutter,
+            source,
+            self.line,
+            self.column,
+            gutter,
+            line_number,
+            line,
+            gutter,
+            padding,
+            carets
+        );
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "day {}, line {}, column {}: {}",
+            self.day, self.line, self.column, self.message
+        )?;
+        if !self.token.is_empty() {
+            write!(f, " (found `{}`)", self.token)?;
+        }
+        return Ok(());
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    Parse(ParseError),
+    // The input is valid but the puzzle has no answer
+    NoSolution { day: u8, message: String },
+}
+
+impl Error {
+    pub fn no_solution(day: u8, message: impl Into<String>) -> Self {
+        return Error::NoSolution {
+            day,
+            message: message.into(),
+        };
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Self {
+        return Error::Parse(error);
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            Error::Parse(error) => write!(f, "{}", error),
+            Error::NoSolution { day, message } => write!(f, "day {}: {}", day, message),
+        };
+    }
+}
+
+impl std::error::Error for Error {}
+
+// Column of a token inside its line, starting at 1
+fn column_of(line: &str, token: &str) -> usize {
+    let line_start = line.as_ptr() as usize;
+    let token_start = token.as_ptr() as usize;
+    if token_start < line_start || token_start + token.len() > line_start + line.len() {
+        return 1;
+    }
+    return line[..token_start - line_start].chars().count() + 1;
+}
+
+// Parse a token of a line, like a number, with a nice error if it fails
+pub fn parse_token<T: FromStr>(
+    day: u8,
+    line_index: usize,
+    line: &str,
+    token: &str,
+    what: &str,
+) -> Result<T, ParseError> {
+    return token.trim().parse::<T>().map_err(|_| {
+        ParseError::at_token(day, line_index, line, token, format!("invalid {}", what))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_of() {
+        let line = "9,4 -> 3,4";
+        assert_eq!(column_of(line, &line[0..1]), 1);
+        assert_eq!(column_of(line, &line[7..8]), 8);
+        assert_eq!(column_of(line, &String::from("4")), 1);
+        let line = "é,x";
+        assert_eq!(column_of(line, &line[3..]), 3);
+    }
+
+    #[test]
+    fn test_parse_token() {
+        let line = "forward 5";
+        assert_eq!(parse_token::<i64>(2, 0, line, &line[8..], "units"), Ok(5));
+        let line = "forward five";
+        assert_eq!(
+            parse_token::<i64>(2, 3, line, &line[8..], "units"),
+            Err(ParseError::new(2, 4, 9, "five", "invalid units"))
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let data = "0,9 -> 5,9\n8,0 -> 0,8\n9,4 -> x,4";
+        let error = ParseError::new(5, 3, 8, "x", "invalid x2");
+        assert_eq!(
+            error.render("inputs/day_05.txt", data),
+            "error: day 5: invalid x2
+ --> inputs/day_05.txt:3:8
+  |
+3 | 9,4 -> x,4
+  |        ^"
+        );
+        assert_eq!(
+            error.to_string(),
+            "day 5, line 3, column 8: invalid x2 (found `x`)"
+        );
+    }
+}
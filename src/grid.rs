@@ -0,0 +1,392 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use crate::error::ParseError;
+
+// Up, left, right and down, the diagonals are added for the 8 neighbours
+const NEIGHBOURS_4: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+const NEIGHBOURS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+// What is after the last row or column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Edges {
+    // Nothing, the cells on the edges have fewer neighbours
+    #[default]
+    Bounded,
+    // The first row or column, like on a torus
+    Wrapping,
+}
+
+// One of two iterators, so a function can return either of them without a Vec
+#[derive(Debug, Clone)]
+pub enum Either<A, B> {
+    Left(A),
+    Right(B),
+}
+
+impl<A: Iterator, B: Iterator<Item = A::Item>> Iterator for Either<A, B> {
+    type Item = A::Item;
+
+    fn next(&mut self) -> Option<A::Item> {
+        return match self {
+            Either::Left(left) => left.next(),
+            Either::Right(right) => right.next(),
+        };
+    }
+}
+
+/**
+ * A rectangular grid, stored row by row.
+ *
+ * Points are `(x, y)` with `x` the column and `y` the row, starting at the
+ * top left corner. Indexing out of the grid panics, `get` doesn't.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        return Grid {
+            width,
+            height,
+            cells: vec![value; width * height],
+        };
+    }
+
+    // The cells are row by row, None if there are not width * height of them
+    pub fn from_vec(width: usize, height: usize, cells: Vec<T>) -> Option<Self> {
+        if cells.len() != width * height {
+            return None;
+        }
+        return Some(Grid {
+            width,
+            height,
+            cells,
+        });
+    }
+
+    pub fn width(&self) -> usize {
+        return self.width;
+    }
+
+    pub fn height(&self) -> usize {
+        return self.height;
+    }
+
+    pub fn len(&self) -> usize {
+        return self.cells.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.cells.is_empty();
+    }
+
+    pub fn contains(&self, (x, y): (usize, usize)) -> bool {
+        return x < self.width && y < self.height;
+    }
+
+    pub fn get(&self, point: (usize, usize)) -> Option<&T> {
+        if !self.contains(point) {
+            return None;
+        }
+        return self.cells.get(point.1 * self.width + point.0);
+    }
+
+    pub fn get_mut(&mut self, point: (usize, usize)) -> Option<&mut T> {
+        if !self.contains(point) {
+            return None;
+        }
+        return self.cells.get_mut(point.1 * self.width + point.0);
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        return self.cells.iter();
+    }
+
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        return self.cells.iter_mut();
+    }
+
+    // All the points, row by row
+    pub fn points(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        return (0..self.len()).map(move |index| (index % width, index / width));
+    }
+
+    pub fn indexed_iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        return self.points().zip(self.cells.iter());
+    }
+
+    pub fn rows(&self) -> std::slice::Chunks<'_, T> {
+        return self.cells.chunks(self.width.max(1));
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        return Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        };
+    }
+
+    // Up, left, right and down, when they are in the grid
+    pub fn neighbours_4(&self, point: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        return self.neighbours(point, &NEIGHBOURS_4);
+    }
+
+    // With the diagonals too
+    pub fn neighbours_8(&self, point: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        return self.neighbours(point, &NEIGHBOURS_8);
+    }
+
+    // Like on a torus, the edges are connected to the opposite ones.
+    // In grids smaller than 3x3 a cell is never its own neighbour, nor twice the same one.
+    pub fn neighbours_4_wrapping(
+        &self,
+        point: (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> {
+        return self.wrapping_neighbours(point, &NEIGHBOURS_4);
+    }
+
+    pub fn neighbours_8_wrapping(
+        &self,
+        point: (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> {
+        return self.wrapping_neighbours(point, &NEIGHBOURS_8);
+    }
+
+    // The 8 neighbours, wrapping or not depending on the edges
+    pub fn neighbours_8_with(
+        &self,
+        point: (usize, usize),
+        edges: Edges,
+    ) -> impl Iterator<Item = (usize, usize)> {
+        return match edges {
+            Edges::Bounded => Either::Left(self.neighbours_8(point)),
+            Edges::Wrapping => Either::Right(self.neighbours_8_wrapping(point)),
+        };
+    }
+
+    fn wrapping_neighbours(
+        &self,
+        (x, y): (usize, usize),
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let (width, height) = (self.width as isize, self.height as isize);
+        let wrap = move |&(dx, dy): &(isize, isize)| {
+            return (
+                (x as isize + dx).rem_euclid(width) as usize,
+                (y as isize + dy).rem_euclid(height) as usize,
+            );
+        };
+        // From 3x3, the offsets all give different neighbours
+        if self.width >= 3 && self.height >= 3 {
+            return Either::Left(offsets.iter().map(wrap));
+        }
+        let mut neighbours = [(0, 0); NEIGHBOURS_8.len()];
+        let mut count = 0;
+        for neighbour in offsets.iter().map(wrap) {
+            if neighbour != (x, y) && !neighbours[..count].contains(&neighbour) {
+                neighbours[count] = neighbour;
+                count += 1;
+            }
+        }
+        return Either::Right(neighbours.into_iter().take(count));
+    }
+
+    fn neighbours(
+        &self,
+        (x, y): (usize, usize),
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let (width, height) = (self.width, self.height);
+        return offsets.iter().filter_map(move |&(dx, dy)| {
+            let neighbour = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+            if neighbour.0 >= width || neighbour.1 >= height {
+                return None;
+            }
+            return Some(neighbour);
+        });
+    }
+}
+
+impl Grid<u8> {
+    /**
+     * Parse a grid of digits, one row per line. `what` is the plural name of
+     * the digits in the errors, like "heights".
+     */
+    pub fn from_digits(day: u8, data: &str, what: &str) -> Result<Grid<u8>, ParseError> {
+        let mut width: Option<usize> = None;
+        let mut cells: Vec<u8> = vec![];
+        let mut height = 0;
+        for (line_index, line) in data.lines().enumerate() {
+            for (index, c) in line.char_indices() {
+                let digit = c.to_digit(10).ok_or_else(|| {
+                    ParseError::at_token(
+                        day,
+                        line_index,
+                        line,
+                        &line[index..index + c.len_utf8()],
+                        format!("{} are digits", what),
+                    )
+                })?;
+                cells.push(digit as u8);
+            }
+            if *width.get_or_insert(line.len()) != line.len() {
+                return Err(ParseError::at_token(
+                    day,
+                    line_index,
+                    line,
+                    line,
+                    format!("expected {} {}", width.unwrap_or(0), what),
+                ));
+            }
+            height += 1;
+        }
+
+        let width = width.unwrap_or(0);
+        if width == 0 {
+            return Err(ParseError::new(day, 1, 1, "", "empty grid"));
+        }
+        return Ok(Grid {
+            width,
+            height,
+            cells,
+        });
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: (usize, usize)) -> &T {
+        return self.get(point).unwrap_or_else(|| {
+            panic!(
+                "{:?} is outside of the {}x{} grid",
+                point, self.width, self.height
+            )
+        });
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, point: (usize, usize)) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        return self
+            .get_mut(point)
+            .unwrap_or_else(|| panic!("{:?} is outside of the {}x{} grid", point, width, height));
+    }
+}
+
+// Digits are printed like in the puzzles, larger values are aligned in columns
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cells = self.map(|cell| cell.to_string());
+        let cell_width = cells.iter().map(|cell| cell.chars().count()).max();
+        let separator = if cell_width.unwrap_or(0) > 1 { " " } else { "" };
+        for (row_index, row) in cells.rows().enumerate() {
+            if row_index > 0 {
+                writeln!(f)?;
+            }
+            let row = row
+                .iter()
+                .map(|cell| format!("{:>1$}", cell, cell_width.unwrap_or(0)))
+                .collect::<Vec<String>>();
+            write!(f, "{}", row.join(separator))?;
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_digits() {
+        let grid = Grid::from_digits(9, "219\n398\n", "heights").unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(2, 0)], 9);
+        assert_eq!(grid[(0, 1)], 3);
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid.get((0, 2)), None);
+        assert_eq!(grid.to_string(), "219\n398");
+
+        assert_eq!(
+            Grid::from_digits(9, "219\n3x8", "heights"),
+            Err(ParseError::new(9, 2, 2, "x", "heights are digits"))
+        );
+        assert_eq!(
+            Grid::from_digits(9, "219\n39", "heights"),
+            Err(ParseError::new(9, 2, 1, "39", "expected 3 heights"))
+        );
+        assert!(Grid::from_digits(9, "", "heights").is_err());
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::new(3, 3, 0);
+        assert_eq!(
+            grid.neighbours_4((1, 1)).collect::<Vec<_>>(),
+            [(1, 0), (0, 1), (2, 1), (1, 2)]
+        );
+        assert_eq!(
+            grid.neighbours_4((0, 0)).collect::<Vec<_>>(),
+            [(1, 0), (0, 1)]
+        );
+        assert_eq!(grid.neighbours_8((1, 1)).count(), 8);
+        assert_eq!(
+            grid.neighbours_8((2, 2)).collect::<Vec<_>>(),
+            [(1, 1), (2, 1), (1, 2)]
+        );
+    }
+
+    #[test]
+    fn test_wrapping_neighbours() {
+        let grid = Grid::new(4, 3, 0);
+        assert_eq!(
+            grid.neighbours_4_wrapping((0, 0)).collect::<Vec<_>>(),
+            [(0, 2), (3, 0), (1, 0), (0, 1)]
+        );
+        assert_eq!(grid.neighbours_8_wrapping((3, 2)).count(), 8);
+        assert_eq!(grid.neighbours_8_with((3, 2), Edges::Bounded).count(), 3);
+        assert_eq!(grid.neighbours_8_with((3, 2), Edges::Wrapping).count(), 8);
+
+        // Left and right are the same cell, up and down are the cell itself
+        let grid = Grid::new(2, 1, 0);
+        assert_eq!(
+            grid.neighbours_8_wrapping((0, 0)).collect::<Vec<_>>(),
+            [(1, 0)]
+        );
+        let grid = Grid::new(3, 2, 0);
+        assert_eq!(
+            grid.neighbours_8_wrapping((0, 0)).collect::<Vec<_>>(),
+            [(2, 1), (0, 1), (1, 1), (2, 0), (1, 0)]
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let mut grid = Grid::new(3, 2, 0);
+        grid[(1, 0)] = 12;
+        *grid.get_mut((2, 1)).unwrap() = 7;
+        assert_eq!(grid.to_string(), " 0 12  0\n 0  0  7");
+        assert_eq!(grid.indexed_iter().filter(|(_, &c)| c > 0).count(), 2);
+    }
+}
@@ -0,0 +1,187 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::day_05::{build_layer_map, Layer, Segment};
+
+// Larger maps would be unreadable anyway, and too big to allocate
+pub const MAX_SIZE: u64 = 4096;
+
+pub type Colour = [u8; 3];
+
+// Why a heatmap can't be drawn, the segments themselves are valid
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeatmapError {
+    NoSegment,
+    TooLarge { width: u64, height: u64 },
+}
+
+impl fmt::Display for HeatmapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            HeatmapError::NoSegment => write!(f, "no segment to draw"),
+            HeatmapError::TooLarge { width, height } => write!(
+                f,
+                "the map is {}x{}, more than {}x{}",
+                width, height, MAX_SIZE, MAX_SIZE
+            ),
+        };
+    }
+}
+
+impl std::error::Error for HeatmapError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    // Row by row
+    pixels: Vec<Colour>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize) -> Self {
+        return Image {
+            width,
+            height,
+            pixels: vec![[0, 0, 0]; width * height],
+        };
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Colour {
+        return self.pixels[y * self.width + x];
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, colour: Colour) {
+        self.pixels[y * self.width + x] = colour;
+    }
+
+    /**
+     * Binary PPM, the simplest image format that image viewers can open:
+     * a text header and then the RGB bytes of the pixels.
+     */
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut ppm = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        for pixel in &self.pixels {
+            ppm.extend_from_slice(pixel);
+        }
+        return ppm;
+    }
+
+    pub fn save_ppm(&self, path: &Path) -> io::Result<()> {
+        return fs::write(path, self.to_ppm());
+    }
+}
+
+// Black for nothing, then red, yellow and white for the most overlaps
+pub fn heat_colour(count: u32, max: u32) -> Colour {
+    if count == 0 {
+        return [0, 0, 0];
+    }
+    let t = f64::from(count) / f64::from(max.max(count));
+    let channel = |value: f64| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    return [
+        channel(3.0 * t),
+        channel(3.0 * t - 1.0),
+        channel(3.0 * t - 2.0),
+    ];
+}
+
+/**
+ * The vent lines of day 5 as a heatmap, coloured by the number of lines on
+ * each point. The image covers the segments of the layer, its top left
+ * corner is the smallest x and y.
+ */
+pub fn vent_heatmap(segments: &[Segment], layer: Layer) -> Result<Image, HeatmapError> {
+    // The size comes from the ends of the segments, so it's checked before
+    // going through every point of a huge segment
+    let layer_segments = || segments.iter().filter(|segment| layer.includes(segment));
+    let min_x = layer_segments().map(|s| s.x1.min(s.x2)).min();
+    let min_y = layer_segments().map(|s| s.y1.min(s.y2)).min();
+    let max_x = layer_segments().map(|s| s.x1.max(s.x2)).max();
+    let max_y = layer_segments().map(|s| s.y1.max(s.y2)).max();
+    let (min_x, min_y, max_x, max_y) = match (min_x, min_y, max_x, max_y) {
+        (Some(min_x), Some(min_y), Some(max_x), Some(max_y)) => (min_x, min_y, max_x, max_y),
+        _ => return Err(HeatmapError::NoSegment),
+    };
+
+    // abs_diff + 1 could overflow with i64::MIN and i64::MAX, but it's way above MAX_SIZE
+    let width = min_x.abs_diff(max_x).saturating_add(1);
+    let height = min_y.abs_diff(max_y).saturating_add(1);
+    if width > MAX_SIZE || height > MAX_SIZE {
+        return Err(HeatmapError::TooLarge { width, height });
+    }
+
+    let map = build_layer_map(segments, layer);
+    let max = map.values().copied().max().unwrap_or(1);
+    let mut image = Image::new(width as usize, height as usize);
+    for (&(x, y), &count) in &map {
+        image.set(
+            min_x.abs_diff(x) as usize,
+            min_y.abs_diff(y) as usize,
+            heat_colour(count, max),
+        );
+    }
+    return Ok(image);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day_05::parse_segments;
+
+    #[test]
+    fn test_heat_colour() {
+        assert_eq!(heat_colour(0, 5), [0, 0, 0]);
+        assert_eq!(heat_colour(1, 3), [255, 0, 0]);
+        assert_eq!(heat_colour(2, 3), [255, 255, 0]);
+        assert_eq!(heat_colour(3, 3), [255, 255, 255]);
+    }
+
+    #[test]
+    fn test_vent_heatmap() {
+        let segments = parse_segments("0,9 -> 5,9\n0,9 -> 2,9\n8,0 -> 0,8").unwrap();
+
+        let image = vent_heatmap(&segments, Layer::All).unwrap();
+        assert_eq!((image.width, image.height), (9, 10));
+        assert_eq!(image.get(0, 9), [255, 255, 255]);
+        assert_eq!(image.get(5, 9), [255, 128, 0]);
+        assert_eq!(image.get(1, 1), [0, 0, 0]);
+
+        // Only the diagonal, from y = 0 to 8
+        let image = vent_heatmap(&segments, Layer::Diagonal).unwrap();
+        assert_eq!((image.width, image.height), (9, 9));
+        assert_eq!(image.get(8, 0), [255, 255, 255]);
+        assert_eq!(image.get(0, 0), [0, 0, 0]);
+
+        let ppm = image.to_ppm();
+        assert!(ppm.starts_with(b"P6\n9 9\n255\n"));
+        assert_eq!(ppm.len(), "P6\n9 9\n255\n".len() + 9 * 9 * 3);
+
+        let segments = parse_segments("0,0 -> 10000,0").unwrap();
+        assert_eq!(
+            vent_heatmap(&segments, Layer::Diagonal),
+            Err(HeatmapError::NoSegment)
+        );
+        assert!(vent_heatmap(&segments, Layer::Straight).is_err());
+
+        // Way too many points to go through them
+        let segments = parse_segments("0,0 -> 100000000000,0").unwrap();
+        let error = vent_heatmap(&segments, Layer::All).unwrap_err();
+        assert_eq!(
+            error,
+            HeatmapError::TooLarge {
+                width: 100000000001,
+                height: 1
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "the map is 100000000001x1, more than 4096x4096"
+        );
+        let segments = parse_segments("0,0 -> 1,1\n-100000000000,5 -> 100000000000,5").unwrap();
+        assert!(vent_heatmap(&segments, Layer::Diagonal).is_ok());
+        assert!(vent_heatmap(&segments, Layer::Straight).is_err());
+    }
+}
@@ -0,0 +1,209 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_INPUTS_DIRECTORY: &str = "inputs";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    File(PathBuf),
+    Stdin,
+    Embedded,
+}
+
+impl fmt::Display for InputSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            InputSource::File(path) => write!(f, "{}", path.display()),
+            InputSource::Stdin => write!(f, "<stdin>"),
+            InputSource::Embedded => write!(f, "<embedded>"),
+        };
+    }
+}
+
+#[derive(Debug)]
+pub struct Input {
+    pub data: String,
+    pub source: InputSource,
+}
+
+#[derive(Debug)]
+pub enum InputError {
+    Io(InputSource, io::Error),
+    NotFound { day: u8, path: PathBuf },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            InputError::Io(source, error) => write!(f, "could not read {}: {}", source, error),
+            InputError::NotFound { day, path } => write!(
+                f,
+                "no input for day {}, {} does not exist",
+                day,
+                path.display()
+            ),
+        };
+    }
+}
+
+impl std::error::Error for InputError {}
+
+// An input that is read as it's used, for the ones too large to be loaded at once
+pub struct InputReader {
+    pub reader: Box<dyn BufRead>,
+    pub source: InputSource,
+}
+
+pub fn input_file_name(day: u8) -> String {
+    return format!("day_{:02}.txt", day);
+}
+
+// The inputs are still embedded in the binary unless the feature is disabled,
+// so the binary can run from anywhere.
+#[cfg(feature = "embedded-inputs")]
+pub fn embedded_input(day: u8) -> Option<&'static str> {
+    return match day {
+        1 => Some(include_str!("../inputs/day_01.txt")),
+        2 => Some(include_str!("../inputs/day_02.txt")),
+        3 => Some(include_str!("../inputs/day_03.txt")),
+        4 => Some(include_str!("../inputs/day_04.txt")),
+        5 => Some(include_str!("../inputs/day_05.txt")),
+        6 => Some(include_str!("../inputs/day_06.txt")),
+        7 => Some(include_str!("../inputs/day_07.txt")),
+        8 => Some(include_str!("../inputs/day_08.txt")),
+        9 => Some(include_str!("../inputs/day_09.txt")),
+        10 => Some(include_str!("../inputs/day_10.txt")),
+        11 => Some(include_str!("../inputs/day_11.txt")),
+        12 => Some(include_str!("../inputs/day_12.txt")),
+        _ => None,
+    };
+}
+
+#[cfg(not(feature = "embedded-inputs"))]
+pub fn embedded_input(_day: u8) -> Option<&'static str> {
+    return None;
+}
+
+// "-" means stdin, like many command line tools
+pub fn read_input(path: &Path) -> Result<Input, InputError> {
+    if path == Path::new("-") {
+        let mut data = String::new();
+        io::stdin()
+            .read_to_string(&mut data)
+            .map_err(|error| InputError::Io(InputSource::Stdin, error))?;
+        return Ok(Input {
+            data,
+            source: InputSource::Stdin,
+        });
+    }
+    let source = InputSource::File(path.to_path_buf());
+    return match fs::read_to_string(path) {
+        Ok(data) => Ok(Input { data, source }),
+        Err(error) => Err(InputError::Io(source, error)),
+    };
+}
+
+// Like read_input, without reading anything yet
+pub fn open_input(path: &Path) -> Result<InputReader, InputError> {
+    if path == Path::new("-") {
+        return Ok(InputReader {
+            reader: Box::new(io::stdin().lock()),
+            source: InputSource::Stdin,
+        });
+    }
+    let source = InputSource::File(path.to_path_buf());
+    return match fs::File::open(path) {
+        Ok(file) => Ok(InputReader {
+            reader: Box::new(BufReader::new(file)),
+            source,
+        }),
+        Err(error) => Err(InputError::Io(source, error)),
+    };
+}
+
+// Like find_input
+pub fn find_input_reader(day: u8, inputs_directory: &Path) -> Result<InputReader, InputError> {
+    let path = inputs_directory.join(input_file_name(day));
+    if path.is_file() {
+        return open_input(&path);
+    }
+    if let Some(data) = embedded_input(day) {
+        return Ok(InputReader {
+            reader: Box::new(data.as_bytes()),
+            source: InputSource::Embedded,
+        });
+    }
+    return Err(InputError::NotFound { day, path });
+}
+
+// Look for the input of a day in the inputs directory,
+// and fallback to the embedded inputs if there is nothing there.
+pub fn find_input(day: u8, inputs_directory: &Path) -> Result<Input, InputError> {
+    let path = inputs_directory.join(input_file_name(day));
+    if path.is_file() {
+        return read_input(&path);
+    }
+    if let Some(data) = embedded_input(day) {
+        return Ok(Input {
+            data: data.to_string(),
+            source: InputSource::Embedded,
+        });
+    }
+    return Err(InputError::NotFound { day, path });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_file_name() {
+        assert_eq!(input_file_name(1), "day_01.txt");
+        assert_eq!(input_file_name(12), "day_12.txt");
+    }
+
+    #[test]
+    fn test_find_input() {
+        let input = find_input(7, Path::new(DEFAULT_INPUTS_DIRECTORY)).unwrap();
+        assert_eq!(
+            input.source,
+            InputSource::File(Path::new("inputs").join("day_07.txt"))
+        );
+        assert!(input.data.starts_with("1101,1,29,67"));
+
+        let input = find_input(7, Path::new("does/not/exist"));
+        if cfg!(feature = "embedded-inputs") {
+            assert_eq!(input.unwrap().source, InputSource::Embedded);
+        } else {
+            assert!(matches!(input, Err(InputError::NotFound { day: 7, .. })));
+        }
+
+        let input = find_input(25, Path::new(DEFAULT_INPUTS_DIRECTORY));
+        assert!(matches!(input, Err(InputError::NotFound { day: 25, .. })));
+    }
+
+    #[test]
+    fn test_read_input_error() {
+        let input = read_input(Path::new("does/not/exist.txt"));
+        assert!(matches!(
+            input,
+            Err(InputError::Io(InputSource::File(_), _))
+        ));
+        let input = open_input(Path::new("does/not/exist.txt"));
+        assert!(matches!(
+            input,
+            Err(InputError::Io(InputSource::File(_), _))
+        ));
+    }
+
+    #[test]
+    fn test_find_input_reader() {
+        let mut input = find_input_reader(7, Path::new(DEFAULT_INPUTS_DIRECTORY)).unwrap();
+        let mut first_line = String::new();
+        input.reader.read_line(&mut first_line).unwrap();
+        assert!(first_line.starts_with("1101,1,29,67"));
+        assert!(find_input_reader(25, Path::new(DEFAULT_INPUTS_DIRECTORY)).is_err());
+    }
+}
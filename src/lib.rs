@@ -0,0 +1,34 @@
+//! Advent of code 2021 solutions, as a library.
+//!
+//! Every day is a module implementing [`Solution`], and all of them are
+//! listed in [`registry::DAYS`].
+
+// Explicit returns are the style of this repository
+#![allow(clippy::needless_return)]
+
+pub mod answers;
+pub mod bench;
+pub mod day_01;
+pub mod day_02;
+pub mod day_03;
+pub mod day_04;
+pub mod day_05;
+pub mod day_06;
+pub mod day_07;
+pub mod day_08;
+pub mod day_09;
+pub mod day_10;
+pub mod day_11;
+pub mod day_12;
+pub mod error;
+pub mod grid;
+pub mod heatmap;
+pub mod inputs;
+pub mod registry;
+pub mod report;
+pub mod solution;
+pub mod trajectory;
+
+pub use error::{Error, ParseError};
+pub use registry::{find_day, DAYS};
+pub use solution::{Day, Part, Run, Runs, Solution};
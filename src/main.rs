@@ -1,62 +1,447 @@
-mod day_01;
-mod day_02;
-mod day_03;
-mod day_04;
-mod day_05;
-mod day_06;
-mod day_07;
-mod day_08;
-mod day_09;
-mod day_10;
-mod day_11;
-mod day_12;
-
-fn main() {
-    let day_1_data = include_str!("../inputs/day_01.txt");
-    println!("Day 1, part 1: {}", day_01::day_1_part_1(day_1_data));
-    println!("Day 1, part 2: {}", day_01::day_1_part_2(day_1_data));
-
-    let day_2_data = include_str!("../inputs/day_02.txt");
-    println!("Day 2, part 1: {}", day_02::day_2_part_1(day_2_data));
-    println!("Day 2, part 2: {}", day_02::day_2_part_2(day_2_data));
-
-    let day_3_data = include_str!("../inputs/day_03.txt");
-    println!("Day 3, part 1: {}", day_03::day_3_part_1(day_3_data));
-    println!("Day 3, part 2: {}", day_03::day_3_part_2(day_3_data));
-
-    let day_4_data = include_str!("../inputs/day_04.txt");
-    println!("Day 4, part 1: {}", day_04::day_4_part_1(day_4_data));
-    println!("Day 4, part 2: {}", day_04::day_4_part_2(day_4_data));
-
-    let day_5_data = include_str!("../inputs/day_05.txt");
-    println!("Day 5, part 1: {}", day_05::day_5_part_1(day_5_data));
-    println!("Day 5, part 2: {}", day_05::day_5_part_2(day_5_data));
-
-    let day_6_data = include_str!("../inputs/day_06.txt");
-    println!("Day 6, part 1: {}", day_06::day_6_part_1(day_6_data));
-    println!("Day 6, part 2: {}", day_06::day_6_part_2(day_6_data));
-
-    let day_7_data = include_str!("../inputs/day_07.txt");
-    println!("Day 7, part 1: {}", day_07::day_7_part_1(day_7_data));
-    println!("Day 7, part 2: {}", day_07::day_7_part_2(day_7_data));
-
-    let day_8_data = include_str!("../inputs/day_08.txt");
-    println!("Day 8, part 1: {}", day_08::day_8_part_1(day_8_data));
-    println!("Day 8, part 2: {}", day_08::day_8_part_2(day_8_data));
-
-    let day_9_data = include_str!("../inputs/day_09.txt");
-    println!("Day 9, part 1: {}", day_09::day_9_part_1(day_9_data));
-    println!("Day 9, part 2: {}", day_09::day_9_part_2(day_9_data));
-
-    let day_10_data = include_str!("../inputs/day_10.txt");
-    println!("Day 10, part 1: {}", day_10::day_10_part_1(day_10_data));
-    println!("Day 10, part 2: {}", day_10::day_10_part_2(day_10_data));
-
-    let day_11_data = include_str!("../inputs/day_11.txt");
-    println!("Day 11, part 1: {}", day_11::day_11_part_1(day_11_data));
-    println!("Day 11, part 2: {}", day_11::day_11_part_2(day_11_data));
-
-    let day_12_data = include_str!("../inputs/day_12.txt");
-    println!("Day 12, part 1: {}", day_12::day_12_part_1(day_12_data));
-    println!("Day 12, part 2: {}", day_12::day_12_part_2(day_12_data));
+// Explicit returns are the style of this repository
+#![allow(clippy::needless_return)]
+
+mod cli;
+mod visualize;
+
+use std::process::ExitCode;
+use std::time::Instant;
+
+use advent_of_code_2021_rust::answers::{self, Status, Verification};
+use advent_of_code_2021_rust::bench::{self, Comparison, Measurement};
+use advent_of_code_2021_rust::day_01::{self, Day01, SonarAnalyzer, SonarReport};
+use advent_of_code_2021_rust::day_02::{self, Day02};
+use advent_of_code_2021_rust::day_05::{self, Day05};
+use advent_of_code_2021_rust::day_11::{self, Day11};
+use advent_of_code_2021_rust::grid::Edges;
+use advent_of_code_2021_rust::heatmap;
+use advent_of_code_2021_rust::inputs::{self, Input};
+use advent_of_code_2021_rust::report::{self, Record};
+use advent_of_code_2021_rust::trajectory::Trajectories;
+use advent_of_code_2021_rust::{find_day, Day, Error, ParseError, Part, Run, Solution};
+use clap::Parser;
+use cli::{
+    BenchArgs, Cli, Command, Format, HeatmapArgs, InputArgs, RunArgs, Selection, SonarArgs,
+    TrajectoryArgs, VerifyArgs, VisualizeArgs,
+};
+use rayon::prelude::*;
+
+// Find the selected days and their inputs, before running anything
+fn load_days(selection: &Selection) -> Result<Vec<(&'static Day, Input)>, ExitCode> {
+    let mut days: Vec<&Day> = vec![];
+    for day in selection.selected_days() {
+        match find_day(day) {
+            Some(d) => days.push(d),
+            // --all means all the days that are solved
+            None if selection.all => continue,
+            None => {
+                eprintln!("error: day {} is not solved yet", day);
+                return Err(ExitCode::from(cli::EXIT_UNKNOWN_DAY));
+            }
+        }
+    }
+
+    if selection.inputs.input.is_some() && days.len() != 1 {
+        eprintln!("error: --input can only be used with a single day");
+        return Err(ExitCode::from(cli::EXIT_INVALID_ARGUMENTS));
+    }
+
+    let mut loaded = vec![];
+    for day in days {
+        let input = match &selection.inputs.input {
+            Some(path) => inputs::read_input(path),
+            None => inputs::find_input(day.day, &selection.inputs.inputs_dir),
+        };
+        match input {
+            Ok(input) => loaded.push((day, input)),
+            Err(error) => {
+                eprintln!("error: {}", error);
+                return Err(ExitCode::from(cli::EXIT_INPUT_ERROR));
+            }
+        }
+    }
+    return Ok(loaded);
+}
+
+// For the commands working on a single day
+fn load_input(day: u8, inputs: &InputArgs) -> Result<Input, ExitCode> {
+    let input = match &inputs.input {
+        Some(path) => inputs::read_input(path),
+        None => inputs::find_input(day, &inputs.inputs_dir),
+    };
+    return input.map_err(|error| {
+        eprintln!("error: {}", error);
+        return ExitCode::from(cli::EXIT_INPUT_ERROR);
+    });
+}
+
+fn report_error(error: &Error, input: &Input) -> ExitCode {
+    match error {
+        Error::Parse(error) => {
+            eprintln!("{}", error.render(&input.source.to_string(), &input.data));
+            return ExitCode::from(cli::EXIT_PARSE_ERROR);
+        }
+        _ => {
+            eprintln!("error: {}", error);
+            return ExitCode::from(cli::EXIT_NO_SOLUTION);
+        }
+    }
+}
+
+fn run_day(day: &Day, input: &Input, parts: &[Part]) -> Vec<(Part, Result<Run, Error>)> {
+    match day.run_parts(&input.data, parts) {
+        Ok(results) => return parts.iter().copied().zip(results).collect(),
+        Err(error) => {
+            // The parse error is reported once, for the first part, the
+            // other parts would fail the same way
+            if let Some(&first_part) = parts.first() {
+                return vec![(first_part, Err(error))];
+            }
+            return vec![];
+        }
+    }
+}
+
+fn run(args: &RunArgs) -> ExitCode {
+    let days = match load_days(&args.selection) {
+        Ok(days) => days,
+        Err(exit_code) => return exit_code,
+    };
+    let parts = args.selection.selected_parts();
+    let start = Instant::now();
+
+    // Keep going after a failing day, but remember it for the exit code
+    let mut exit_code = ExitCode::SUCCESS;
+    // The errors are only on stderr, the records are for the parts that worked
+    let mut records: Vec<Record> = vec![];
+
+    let mut print_day = |day: &Day, input: &Input, results: Vec<(Part, Result<Run, Error>)>| {
+        for (part, result) in results {
+            match result {
+                Ok(run) => match args.format {
+                    Format::Text if args.time => println!(
+                        "Day {}, part {}: {} (parse: {:.2?}, solve: {:.2?})",
+                        day.day, part, run.answer, run.parse_time, run.solve_time
+                    ),
+                    Format::Text => println!("Day {}, part {}: {}", day.day, part, run.answer),
+                    Format::Csv => println!("{}", Record::new(day.day, part, &run).to_csv()),
+                    Format::Json => records.push(Record::new(day.day, part, &run)),
+                },
+                Err(error) => exit_code = report_error(&error, input),
+            }
+        }
+    };
+
+    if args.format == Format::Csv {
+        println!("{}", report::CSV_HEADER);
+    }
+    if args.parallel {
+        // All the days at once, but printed in order when they are all done
+        let results = days
+            .par_iter()
+            .map(|(day, input)| run_day(day, input, &parts))
+            .collect::<Vec<_>>();
+        for ((day, input), results) in days.iter().zip(results) {
+            print_day(day, input, results);
+        }
+    } else {
+        for (day, input) in &days {
+            print_day(day, input, run_day(day, input, &parts));
+        }
+    }
+
+    if args.format == Format::Json {
+        println!("{}", report::to_json(&records));
+    }
+    if args.format == Format::Text && args.time {
+        println!("Total: {:.2?}", start.elapsed());
+    }
+    return exit_code;
+}
+
+fn bench(args: &BenchArgs) -> ExitCode {
+    let days = match load_days(&args.selection) {
+        Ok(days) => days,
+        Err(exit_code) => return exit_code,
+    };
+
+    let baseline = match &args.baseline {
+        Some(path) => match bench::load_baseline(path) {
+            Ok(baseline) => Some(baseline),
+            Err(error) => {
+                eprintln!("error: could not read {}: {}", path.display(), error);
+                return ExitCode::from(cli::EXIT_INPUT_ERROR);
+            }
+        },
+        None => None,
+    };
+
+    let mut exit_code = ExitCode::SUCCESS;
+    let mut measurements: Vec<Measurement> = vec![];
+
+    println!(
+        "{:>3} {:>4} {:>12} {:>12} {:>12} {:>10}",
+        "Day", "Part", "min", "median", "p95", "baseline"
+    );
+    let parts = args.selection.selected_parts();
+    for (day, input) in &days {
+        let day_measurements = match bench::bench(day, &input.data, &parts, args.iterations) {
+            Ok(day_measurements) => day_measurements,
+            Err(error) => {
+                exit_code = report_error(&error, input);
+                continue;
+            }
+        };
+        for measurement in day_measurements {
+            let part = measurement.part;
+            let comparison = baseline
+                .as_ref()
+                .and_then(|baseline| baseline.get(&(day.day, part)))
+                .map(|before| Comparison::new(&measurement, before, args.threshold / 100.0));
+            if comparison.is_some_and(|c| c.regression) {
+                exit_code = ExitCode::from(cli::EXIT_REGRESSION);
+            }
+            println!(
+                "{:>3} {:>4} {:>12} {:>12} {:>12} {:>10}",
+                day.day,
+                part,
+                format!("{:.2?}", measurement.min),
+                format!("{:.2?}", measurement.median),
+                format!("{:.2?}", measurement.p95),
+                comparison.map_or(String::from("-"), |c| c.to_string())
+            );
+            measurements.push(measurement);
+        }
+    }
+
+    if let Some(path) = &args.save_baseline {
+        if let Err(error) = bench::save_baseline(path, &measurements) {
+            eprintln!("error: could not write {}: {}", path.display(), error);
+            return ExitCode::FAILURE;
+        }
+    }
+    return exit_code;
+}
+
+fn verify(args: &VerifyArgs) -> ExitCode {
+    let days = match load_days(&args.selection) {
+        Ok(days) => days,
+        Err(exit_code) => return exit_code,
+    };
+
+    let answers = match answers::load_answers(&args.answers) {
+        Ok(answers) => answers,
+        Err(error) => {
+            eprintln!(
+                "error: could not read {}: {}",
+                args.answers.display(),
+                error
+            );
+            return ExitCode::from(cli::EXIT_INPUT_ERROR);
+        }
+    };
+
+    let parts = args.selection.selected_parts();
+    let mut verifications: Vec<Verification> = vec![];
+    for (day, input) in &days {
+        let day_verifications = answers::verify(day, &input.data, &parts, &answers);
+        for (index, verification) in day_verifications.iter().enumerate() {
+            match &verification.actual {
+                // Every part has the same parse error, it's reported once
+                Err(Error::Parse(_)) if index > 0 => {}
+                Err(error) => {
+                    report_error(error, input);
+                }
+                Ok(_) => {}
+            }
+        }
+        verifications.extend(day_verifications);
+    }
+    print!("{}", answers::table(&verifications));
+
+    if verifications.iter().any(|v| v.status() != Status::Correct) {
+        return ExitCode::from(cli::EXIT_WRONG_ANSWER);
+    }
+    return ExitCode::SUCCESS;
+}
+
+fn visualize(args: &VisualizeArgs) -> ExitCode {
+    let input = match load_input(Day11::DAY, &args.inputs) {
+        Ok(input) => input,
+        Err(exit_code) => return exit_code,
+    };
+    let grid = match day_11::parse_data(&input.data) {
+        Ok(grid) => grid,
+        Err(error) => return report_error(&Error::from(error), &input),
+    };
+
+    let edges = if args.wrap {
+        Edges::Wrapping
+    } else {
+        Edges::Bounded
+    };
+    match visualize::play(grid, edges, args.fps, args.paused) {
+        Ok(Some(step)) => println!("All the octopuses flashed at step {}", step),
+        Ok(None) => {}
+        Err(error) => {
+            eprintln!("error: could not use the terminal: {}", error);
+            return ExitCode::FAILURE;
+        }
+    }
+    return ExitCode::SUCCESS;
+}
+
+fn heatmap(args: &HeatmapArgs) -> ExitCode {
+    let input = match load_input(Day05::DAY, &args.inputs) {
+        Ok(input) => input,
+        Err(exit_code) => return exit_code,
+    };
+    let segments = match day_05::parse_segments(&input.data) {
+        Ok(segments) => segments,
+        Err(error) => return report_error(&Error::from(error), &input),
+    };
+
+    let image = match heatmap::vent_heatmap(&segments, args.layer.into()) {
+        Ok(image) => image,
+        Err(error) => {
+            // The segments are valid, there is just no image for them
+            eprintln!("error: {}", error);
+            return ExitCode::from(cli::EXIT_NO_SOLUTION);
+        }
+    };
+    if let Err(error) = image.save_ppm(&args.output) {
+        eprintln!(
+            "error: could not write {}: {}",
+            args.output.display(),
+            error
+        );
+        return ExitCode::from(cli::EXIT_OUTPUT_ERROR);
+    }
+    println!(
+        "{}x{} heatmap written to {}",
+        image.width,
+        image.height,
+        args.output.display()
+    );
+    return ExitCode::SUCCESS;
+}
+
+fn print_sonar_report(report: &SonarReport) {
+    println!(
+        "Window {}: {} depths, {} increases, {} decreases, {} flat",
+        report.window, report.depths, report.increases, report.decreases, report.flat
+    );
+    if let Some(run) = report.longest_increasing_run {
+        println!(
+            "  longest increasing run: {} increases from window {}",
+            run.length, run.start
+        );
+    }
+    if !report.largest_jumps.is_empty() {
+        let jumps = report
+            .largest_jumps
+            .iter()
+            .map(|jump| format!("{:+} at window {}", jump.change, jump.window))
+            .collect::<Vec<String>>();
+        println!("  largest jumps: {}", jumps.join(", "));
+    }
+}
+
+fn sonar(args: &SonarArgs) -> ExitCode {
+    let input = match &args.inputs.input {
+        Some(path) => inputs::open_input(path),
+        None => inputs::find_input_reader(Day01::DAY, &args.inputs.inputs_dir),
+    };
+    let input = match input {
+        Ok(input) => input,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            return ExitCode::from(cli::EXIT_INPUT_ERROR);
+        }
+    };
+
+    // All the windows in a single pass, stdin can only be read once
+    let mut analyzers = args
+        .windows
+        .iter()
+        .map(|&window| SonarAnalyzer::new(window, args.jumps))
+        .collect::<Vec<SonarAnalyzer>>();
+    for depth in day_01::read_depths(input.reader) {
+        let depth = match depth {
+            Ok(depth) => depth,
+            Err(error) => {
+                // Without the whole input, the error can't be rendered with its line
+                if let Some(error) = error.get_ref().and_then(|e| e.downcast_ref::<ParseError>()) {
+                    eprintln!("error: {}: {}", input.source, error);
+                    return ExitCode::from(cli::EXIT_PARSE_ERROR);
+                }
+                eprintln!("error: could not read {}: {}", input.source, error);
+                return ExitCode::from(cli::EXIT_INPUT_ERROR);
+            }
+        };
+        for analyzer in &mut analyzers {
+            analyzer.push(depth);
+        }
+    }
+
+    let reports = analyzers
+        .iter()
+        .map(SonarAnalyzer::report)
+        .collect::<Vec<SonarReport>>();
+    match args.format {
+        Format::Text => reports.iter().for_each(print_sonar_report),
+        Format::Json => println!("{}", report::to_json(&reports)),
+        Format::Csv => {
+            println!("{}", report::SONAR_CSV_HEADER);
+            for sonar_report in &reports {
+                println!("{}", report::sonar_to_csv(sonar_report));
+            }
+        }
+    }
+    return ExitCode::SUCCESS;
+}
+
+fn trajectory(args: &TrajectoryArgs) -> ExitCode {
+    let input = match load_input(Day02::DAY, &args.inputs) {
+        Ok(input) => input,
+        Err(exit_code) => return exit_code,
+    };
+    let instructions = match day_02::parse_submarine_instructions(&input.data) {
+        Ok(instructions) => instructions,
+        Err(error) => return report_error(&Error::from(error), &input),
+    };
+
+    let trajectories = Trajectories::new(&instructions);
+    if args.csv.is_none() && args.svg.is_none() {
+        print!("{}", trajectories.to_csv());
+        return ExitCode::SUCCESS;
+    }
+    let outputs = [
+        (&args.csv, trajectories.to_csv()),
+        (&args.svg, trajectories.to_svg()),
+    ];
+    for (path, contents) in outputs {
+        if let Some(path) = path {
+            if let Err(error) = std::fs::write(path, contents) {
+                eprintln!("error: could not write {}: {}", path.display(), error);
+                return ExitCode::FAILURE;
+            }
+            println!("Trajectories written to {}", path.display());
+        }
+    }
+    return ExitCode::SUCCESS;
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    return match cli.command {
+        Some(Command::Run(args)) => run(&args),
+        Some(Command::Bench(args)) => bench(&args),
+        Some(Command::Verify(args)) => verify(&args),
+        Some(Command::Visualize(args)) => visualize(&args),
+        Some(Command::Heatmap(args)) => heatmap(&args),
+        Some(Command::Sonar(args)) => sonar(&args),
+        Some(Command::Trajectory(args)) => trajectory(&args),
+        None => run(&RunArgs::all()),
+    };
 }
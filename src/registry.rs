@@ -0,0 +1,42 @@
+use crate::solution::Day;
+use crate::{
+    day_01, day_02, day_03, day_04, day_05, day_06, day_07, day_08, day_09, day_10, day_11, day_12,
+};
+
+// Sorted by day, a new day only needs to be added here
+pub const DAYS: &[Day] = &[
+    Day::of::<day_01::Day01>(),
+    Day::of::<day_02::Day02>(),
+    Day::of::<day_03::Day03>(),
+    Day::of::<day_04::Day04>(),
+    Day::of::<day_05::Day05>(),
+    Day::of::<day_06::Day06>(),
+    Day::of::<day_07::Day07>(),
+    Day::of::<day_08::Day08>(),
+    Day::of::<day_09::Day09>(),
+    Day::of::<day_10::Day10>(),
+    Day::of::<day_11::Day11>(),
+    Day::of::<day_12::Day12>(),
+];
+
+pub fn find_day(day: u8) -> Option<&'static Day> {
+    return DAYS.iter().find(|d| d.day == day);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_days_are_sorted_and_unique() {
+        for window in DAYS.windows(2) {
+            assert!(window[0].day < window[1].day, "{:?}", window);
+        }
+    }
+
+    #[test]
+    fn test_find_day() {
+        assert_eq!(find_day(4).unwrap().title, "Giant Squid");
+        assert!(find_day(25).is_none());
+    }
+}
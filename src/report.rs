@@ -0,0 +1,115 @@
+use serde::Serialize;
+
+use crate::day_01::SonarReport;
+use crate::solution::{Part, Run};
+
+/**
+ * The result of a part in a machine readable form, for the `--format json`
+ * and `--format csv` outputs. The times are in nanoseconds.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Record {
+    pub day: u8,
+    pub part: u8,
+    pub answer: i64,
+    pub parse_ns: u64,
+    pub solve_ns: u64,
+}
+
+pub const CSV_HEADER: &str = "day,part,answer,parse_ns,solve_ns";
+
+impl Record {
+    pub fn new(day: u8, part: Part, run: &Run) -> Self {
+        // u64 nanoseconds is more than 500 years
+        let nanos = |duration: std::time::Duration| {
+            return u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        };
+        return Record {
+            day,
+            part: part.number(),
+            answer: run.answer,
+            parse_ns: nanos(run.parse_time),
+            solve_ns: nanos(run.solve_time),
+        };
+    }
+
+    // Only numbers, nothing to escape
+    pub fn to_csv(&self) -> String {
+        return format!(
+            "{},{},{},{},{}",
+            self.day, self.part, self.answer, self.parse_ns, self.solve_ns
+        );
+    }
+}
+
+// The largest jumps are window:change pairs separated by spaces, in one column
+pub const SONAR_CSV_HEADER: &str =
+    "window,depths,increases,decreases,flat,longest_run_start,longest_run_length,largest_jumps";
+
+pub fn sonar_to_csv(report: &SonarReport) -> String {
+    let (run_start, run_length) = match report.longest_increasing_run {
+        Some(run) => (run.start.to_string(), run.length.to_string()),
+        None => (String::new(), String::from("0")),
+    };
+    let jumps = report
+        .largest_jumps
+        .iter()
+        .map(|jump| format!("{}:{}", jump.window, jump.change))
+        .collect::<Vec<String>>();
+    return format!(
+        "{},{},{},{},{},{},{},{}",
+        report.window,
+        report.depths,
+        report.increases,
+        report.decreases,
+        report.flat,
+        run_start,
+        run_length,
+        jumps.join(" ")
+    );
+}
+
+// For the records and the sonar reports
+pub fn to_json<T: Serialize>(records: &[T]) -> String {
+    return serde_json::to_string_pretty(records).expect("Records are always valid JSON");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day_01;
+    use std::time::Duration;
+
+    #[test]
+    fn test_records() {
+        let run = Run {
+            answer: 5934,
+            parse_time: Duration::from_micros(3),
+            solve_time: Duration::from_nanos(1500),
+        };
+        let record = Record::new(6, Part::Two, &run);
+        assert_eq!(record.to_csv(), "6,2,5934,3000,1500");
+        assert_eq!(
+            to_json(&[record]),
+            r#"[
+  {
+    "day": 6,
+    "part": 2,
+    "answer": 5934,
+    "parse_ns": 3000,
+    "solve_ns": 1500
+  }
+]"#
+        );
+        assert_eq!(to_json::<Record>(&[]), "[]");
+    }
+
+    #[test]
+    fn test_sonar_report() {
+        let report = day_01::sonar_report([199, 200, 208, 210, 200, 207], 1, 2);
+        assert_eq!(sonar_to_csv(&report), "1,6,4,1,0,0,3,4:-10 2:8");
+        let report = day_01::sonar_report([3, 2], 1, 0);
+        assert_eq!(sonar_to_csv(&report), "1,2,0,1,0,,0,");
+        assert!(to_json(&[report]).contains(r#""longest_increasing_run": null,"#));
+    }
+}
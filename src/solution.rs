@@ -0,0 +1,135 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::error::{Error, ParseError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+
+    pub fn number(self) -> u8 {
+        return match self {
+            Part::One => 1,
+            Part::Two => 2,
+        };
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Like a number, so it can be aligned
+        return fmt::Display::fmt(&self.number(), f);
+    }
+}
+
+/**
+ * A day of the advent of code.
+ *
+ * The input is parsed once and shared by both parts, see Day::run_parts.
+ * Inputs can borrow the puzzle data, which is why it's a generic associated
+ * type.
+ *
+ * Invalid inputs are parse errors, the parts only fail when the puzzle has
+ * no answer.
+ */
+pub trait Solution {
+    const DAY: u8;
+    const TITLE: &'static str;
+
+    type Input<'a>;
+
+    fn parse(data: &str) -> Result<Self::Input<'_>, ParseError>;
+    fn part_1(input: &Self::Input<'_>) -> Result<i64, Error>;
+    fn part_2(input: &Self::Input<'_>) -> Result<i64, Error>;
+
+    fn solve_part_1(data: &str) -> Result<i64, Error> {
+        return Self::part_1(&Self::parse(data)?);
+    }
+
+    fn solve_part_2(data: &str) -> Result<i64, Error> {
+        return Self::part_2(&Self::parse(data)?);
+    }
+}
+
+// An answer and how long it took to get it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Run {
+    pub answer: i64,
+    pub parse_time: Duration,
+    pub solve_time: Duration,
+}
+
+impl Run {
+    pub fn total_time(&self) -> Duration {
+        return self.parse_time + self.solve_time;
+    }
+}
+
+// The runs of some parts, in the same order
+pub type Runs = Vec<Result<Run, Error>>;
+
+// Solution can't be used as a trait object because of its associated types,
+// so the registry stores days with their types erased.
+#[derive(Clone, Copy)]
+pub struct Day {
+    pub day: u8,
+    pub title: &'static str,
+    run: fn(&str, &[Part]) -> Result<Runs, Error>,
+}
+
+impl Day {
+    pub const fn of<S: Solution>() -> Day {
+        return Day {
+            day: S::DAY,
+            title: S::TITLE,
+            run: run_solution::<S>,
+        };
+    }
+
+    pub fn run(&self, data: &str, part: Part) -> Result<i64, Error> {
+        return self.run_timed(data, part).map(|run| run.answer);
+    }
+
+    pub fn run_timed(&self, data: &str, part: Part) -> Result<Run, Error> {
+        return self.run_parts(data, &[part])?.remove(0);
+    }
+
+    // Parses the input once for all the parts, the outer error is the parse error
+    pub fn run_parts(&self, data: &str, parts: &[Part]) -> Result<Runs, Error> {
+        return (self.run)(data, parts);
+    }
+}
+
+impl fmt::Debug for Day {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "Day {}: {}", self.day, self.title);
+    }
+}
+
+// Every run has the time of the single parse
+fn run_solution<S: Solution>(data: &str, parts: &[Part]) -> Result<Runs, Error> {
+    let start = Instant::now();
+    let input = S::parse(data)?;
+    let parse_time = start.elapsed();
+
+    let run_part = |part: Part| {
+        let start = Instant::now();
+        let answer = match part {
+            Part::One => S::part_1(&input)?,
+            Part::Two => S::part_2(&input)?,
+        };
+        let solve_time = start.elapsed();
+
+        return Ok(Run {
+            answer,
+            parse_time,
+            solve_time,
+        });
+    };
+    return Ok(parts.iter().map(|&part| run_part(part)).collect());
+}
@@ -0,0 +1,170 @@
+use crate::day_02::{trajectory, Instruction, Plain, Position, WithAim};
+
+const WIDTH: f64 = 800.0;
+const PANEL_HEIGHT: f64 = 300.0;
+const MARGIN: f64 = 50.0;
+
+pub const CSV_HEADER: &str = "step,horizontal,depth,aim_horizontal,aim_depth,aim";
+
+/**
+ * The positions of the submarine with the movements of both parts on the
+ * same instructions. The step 0 is the starting point, the step i is the
+ * position after the i-th instruction.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trajectories {
+    pub plain: Vec<Position>,
+    pub with_aim: Vec<Position>,
+}
+
+impl Trajectories {
+    pub fn new(instructions: &[Instruction]) -> Self {
+        return Trajectories {
+            plain: trajectory(Plain, instructions),
+            with_aim: trajectory(WithAim, instructions),
+        };
+    }
+
+    // The aim of the plain movement is always 0, so it's not a column
+    pub fn to_csv(&self) -> String {
+        let mut csv = format!("{}\n", CSV_HEADER);
+        for (step, (plain, with_aim)) in self.plain.iter().zip(&self.with_aim).enumerate() {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{}\n",
+                step,
+                plain.horizontal,
+                plain.depth,
+                with_aim.horizontal,
+                with_aim.depth,
+                with_aim.aim
+            ));
+        }
+        return csv;
+    }
+
+    /**
+     * The depth along the horizontal position, one panel per part. The
+     * depths of part 2 are way larger so each panel has its own scale,
+     * and deeper is lower like in the sea.
+     */
+    pub fn to_svg(&self) -> String {
+        let height = 2.0 * PANEL_HEIGHT;
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+            WIDTH, height, WIDTH, height
+        );
+        svg.push_str("<rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n");
+        svg.push_str(&panel(&self.plain, "Part 1", "#1f77b4", 0.0));
+        svg.push_str(&panel(
+            &self.with_aim,
+            "Part 2, with aim",
+            "#d62728",
+            PANEL_HEIGHT,
+        ));
+        svg.push_str("</svg>\n");
+        return svg;
+    }
+}
+
+// The smallest and largest values, a range of 1 when they are all the same
+fn bounds(values: impl Iterator<Item = i64> + Clone) -> (f64, f64) {
+    let min = values.clone().min().unwrap_or(0) as f64;
+    let max = values.max().unwrap_or(0) as f64;
+    if min == max {
+        return (min, min + 1.0);
+    }
+    return (min, max);
+}
+
+fn panel(positions: &[Position], title: &str, colour: &str, top: f64) -> String {
+    let (min_x, max_x) = bounds(positions.iter().map(|position| position.horizontal));
+    let (min_y, max_y) = bounds(positions.iter().map(|position| position.depth));
+    let (left, right) = (MARGIN, WIDTH - MARGIN / 2.0);
+    let (upper, lower) = (top + MARGIN, top + PANEL_HEIGHT - MARGIN / 2.0);
+    let scale_x = |x: f64| left + (x - min_x) / (max_x - min_x) * (right - left);
+    let scale_y = |y: f64| upper + (y - min_y) / (max_y - min_y) * (lower - upper);
+
+    let points = positions
+        .iter()
+        .map(|position| {
+            return format!(
+                "{:.1},{:.1}",
+                scale_x(position.horizontal as f64),
+                scale_y(position.depth as f64)
+            );
+        })
+        .collect::<Vec<String>>();
+
+    let mut svg = String::new();
+    svg.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"16\">{}</text>\n",
+        left,
+        top + MARGIN / 2.0,
+        title
+    ));
+    // The axes, with the bounds of the values on them
+    svg.push_str(&format!(
+        "<path d=\"M{} {} V{} H{}\" fill=\"none\" stroke=\"black\"/>\n",
+        left, upper, lower, right
+    ));
+    for (x, y, anchor, label) in [
+        (left - 5.0, upper + 4.0, "end", min_y),
+        (left - 5.0, lower, "end", max_y),
+        (left, lower + 15.0, "start", min_x),
+        (right, lower + 15.0, "end", max_x),
+    ] {
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"{}\" font-family=\"sans-serif\" font-size=\"10\">{}</text>\n",
+            x, y, anchor, label
+        ));
+    }
+    svg.push_str(&format!(
+        "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"1.5\"/>\n",
+        points.join(" "),
+        colour
+    ));
+    return svg;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day_02::parse_submarine_instructions;
+
+    const EXAMPLE: &str = "forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2";
+
+    #[test]
+    fn test_csv() {
+        let trajectories = Trajectories::new(&parse_submarine_instructions(EXAMPLE).unwrap());
+        assert_eq!(trajectories.plain.len(), 7);
+        assert_eq!(
+            trajectories.to_csv(),
+            "step,horizontal,depth,aim_horizontal,aim_depth,aim
+0,0,0,0,0,0
+1,5,0,5,0,0
+2,5,5,5,0,5
+3,13,5,13,40,5
+4,13,2,13,40,2
+5,13,10,13,40,10
+6,15,10,15,60,10
+"
+        );
+    }
+
+    #[test]
+    fn test_svg() {
+        let trajectories = Trajectories::new(&parse_submarine_instructions(EXAMPLE).unwrap());
+        let svg = trajectories.to_svg();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<polyline").count(), 2);
+        // The start is at the top left of both panels, the end at the right
+        assert!(svg.contains("points=\"50.0,50.0 "));
+        assert!(svg.contains("points=\"50.0,350.0 "));
+        assert!(svg.contains(" 775.0,575.0\""));
+
+        // Nothing moves, but it's still a valid plot
+        let svg = Trajectories::new(&[]).to_svg();
+        assert!(svg.contains("points=\"50.0,50.0\""));
+    }
+}
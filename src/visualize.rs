@@ -0,0 +1,195 @@
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Color, Stylize};
+use crossterm::{cursor, queue, terminal};
+
+use advent_of_code_2021_rust::day_11::{self, Flash};
+use advent_of_code_2021_rust::grid::{Edges, Grid};
+
+const MIN_FPS: f64 = 0.5;
+const MAX_FPS: f64 = 120.0;
+
+// The cells that flashed are highlighted, the others are brighter with more energy
+pub fn render_frame(grid: &Grid<u8>, flashes: &[Flash]) -> String {
+    let mut flashed = Grid::new(grid.width(), grid.height(), false);
+    for flash in flashes {
+        flashed[flash.point] = true;
+    }
+
+    let mut frame = String::new();
+    for (point, &energy) in grid.indexed_iter() {
+        if point.0 == 0 && point.1 > 0 {
+            frame.push('\n');
+        }
+        let digit = energy.to_string();
+        if flashed[point] {
+            frame.push_str(&digit.bold().black().on(Color::Yellow).to_string());
+        } else {
+            // The grays of the 256 colours palette go from 232 to 255
+            let gray = 236 + 2 * energy.min(9);
+            frame.push_str(&digit.with(Color::AnsiValue(gray)).to_string());
+        }
+    }
+    return frame;
+}
+
+struct State {
+    step: usize,
+    total_flashes: usize,
+    flashes: Vec<Flash>,
+    paused: bool,
+    fps: f64,
+    synchronised: bool,
+}
+
+fn draw(out: &mut impl Write, grid: &Grid<u8>, state: &State) -> io::Result<()> {
+    queue!(
+        out,
+        cursor::MoveTo(0, 0),
+        terminal::Clear(terminal::ClearType::All)
+    )?;
+    // Raw mode, the lines have to go back to the first column themselves
+    write!(
+        out,
+        "{}\r\n\r\n",
+        render_frame(grid, &state.flashes).replace('\n', "\r\n")
+    )?;
+    write!(
+        out,
+        "step {}, {} flashes, {} in total\r\n",
+        state.step,
+        state.flashes.len(),
+        state.total_flashes
+    )?;
+    if state.synchronised {
+        write!(
+            out,
+            "all the octopuses flashed at step {}, q to quit\r\n",
+            state.step
+        )?;
+    } else {
+        write!(
+            out,
+            "{} at {} fps, space: play/pause, n: next step, +/-: speed, q: quit\r\n",
+            if state.paused { "paused" } else { "playing" },
+            state.fps
+        )?;
+    }
+    return out.flush();
+}
+
+// Puts the terminal back in its normal state, even after an error
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enter(out: &mut impl Write) -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        let raw = RawTerminal;
+        queue!(out, terminal::EnterAlternateScreen, cursor::Hide)?;
+        return Ok(raw);
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let mut out = io::stdout();
+        let _ = queue!(out, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = out.flush();
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn is_quit(key: &KeyEvent) -> bool {
+    return matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+        || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL));
+}
+
+/**
+ * Play the day 11 simulation in the terminal until the first step where all
+ * the octopuses flash, or until q is pressed. Returns that step if it was
+ * reached.
+ */
+pub fn play(mut grid: Grid<u8>, edges: Edges, fps: f64, paused: bool) -> io::Result<Option<usize>> {
+    let mut out = io::stdout();
+    let _raw = RawTerminal::enter(&mut out)?;
+    let mut state = State {
+        step: 0,
+        total_flashes: 0,
+        flashes: vec![],
+        paused,
+        fps: fps.clamp(MIN_FPS, MAX_FPS),
+        synchronised: false,
+    };
+
+    loop {
+        draw(&mut out, &grid, &state)?;
+
+        let mut next_step = false;
+        // Wait for a key when there is nothing to play, or for the next frame
+        let waiting = state.paused || state.synchronised;
+        let key = if waiting || event::poll(Duration::from_secs_f64(1.0 / state.fps))? {
+            Some(event::read()?)
+        } else {
+            next_step = true;
+            None
+        };
+
+        if let Some(Event::Key(key)) = key {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if is_quit(&key) {
+                break;
+            }
+            match key.code {
+                KeyCode::Char(' ') => state.paused = !state.paused,
+                KeyCode::Char('n') | KeyCode::Right => next_step = state.paused,
+                KeyCode::Char('+') => state.fps = (state.fps * 2.0).min(MAX_FPS),
+                KeyCode::Char('-') => state.fps = (state.fps / 2.0).max(MIN_FPS),
+                _ => {}
+            }
+        }
+
+        if next_step && !state.synchronised {
+            state.flashes = day_11::step(&mut grid, edges);
+            state.step += 1;
+            state.total_flashes += state.flashes.len();
+            state.synchronised = state.flashes.len() == grid.len();
+        }
+    }
+
+    return Ok(if state.synchronised {
+        Some(state.step)
+    } else {
+        None
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_frame() {
+        let mut grid = day_11::parse_data("19\n11").unwrap();
+        let flashes = day_11::step(&mut grid, Edges::Bounded);
+        let frame = render_frame(&grid, &flashes);
+
+        assert_eq!(frame.lines().count(), 2);
+        let (first_row, second_row) = frame.split_once('\n').unwrap();
+        assert_eq!(
+            first_row,
+            format!(
+                "{}{}",
+                "3".with(Color::AnsiValue(242)),
+                "0".bold().black().on(Color::Yellow)
+            )
+        );
+        assert_eq!(
+            second_row,
+            "3".with(Color::AnsiValue(242)).to_string().repeat(2)
+        );
+    }
+}
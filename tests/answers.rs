@@ -0,0 +1,24 @@
+// The answers of the real inputs must not change when refactoring
+
+use std::path::Path;
+
+use advent_of_code_2021_rust::answers::{self, Status};
+use advent_of_code_2021_rust::inputs::find_input;
+use advent_of_code_2021_rust::{Part, DAYS};
+
+#[test]
+fn test_recorded_answers() {
+    let answers = answers::load_answers(Path::new("answers.toml")).expect("answers.toml");
+
+    let mut verifications = vec![];
+    for day in DAYS {
+        let input = find_input(day.day, Path::new("inputs")).expect("missing input");
+        verifications.extend(answers::verify(day, &input.data, &Part::ALL, &answers));
+    }
+
+    assert!(
+        verifications.iter().all(|v| v.status() == Status::Correct),
+        "some answers changed:\n{}",
+        answers::table(&verifications)
+    );
+}
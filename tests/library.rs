@@ -0,0 +1,47 @@
+// The days can be used from outside the crate
+
+use advent_of_code_2021_rust::day_02::{parse_submarine_instructions, Direction, Instruction};
+use advent_of_code_2021_rust::day_05::parse_segments;
+use advent_of_code_2021_rust::{find_day, Part, Solution, DAYS};
+
+#[test]
+fn test_parsers_are_public() {
+    let instructions = parse_submarine_instructions("forward 5\ndown 3").unwrap();
+    assert_eq!(instructions.len(), 2);
+    assert_eq!(
+        instructions[0],
+        Instruction::Move {
+            direction: Direction::Forward,
+            units: 5
+        }
+    );
+    assert!(matches!(
+        instructions[1],
+        Instruction::Move { units: 3, .. }
+    ));
+
+    let segments = parse_segments("0,9 -> 5,9").unwrap();
+    assert_eq!((segments[0].x1, segments[0].y2), (0, 9));
+}
+
+#[test]
+fn test_registry_is_public() {
+    assert_eq!(DAYS[0].day, 1);
+    let day = find_day(6).expect("day 6 is solved");
+    assert_eq!(day.run("3,4,3,1,2", Part::One), Ok(5934));
+    assert_eq!(
+        advent_of_code_2021_rust::day_06::Day06::solve_part_2("3,4,3,1,2"),
+        Ok(26984457539)
+    );
+
+    // Parsed once for both parts
+    let runs = day.run_parts("3,4,3,1,2", &Part::ALL).unwrap();
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs[0].as_ref().unwrap().answer, 5934);
+    assert_eq!(runs[1].as_ref().unwrap().answer, 26984457539);
+    assert_eq!(
+        runs[0].as_ref().unwrap().parse_time,
+        runs[1].as_ref().unwrap().parse_time
+    );
+    assert!(day.run_parts("3,x", &Part::ALL).is_err());
+}